@@ -61,21 +61,21 @@ export default function DesktopLayout() {
                 {engineHealth && (
                   <div
                     className={`px-2 py-1 text-xs rounded flex items-center space-x-1 ${
-                      engineHealth.engineConfigured
+                      engineHealth.engine_configured
                         ? 'bg-green-100 text-green-700'
-                        : engineHealth.ollamaAvailable
+                        : engineHealth.ollama_available
                         ? 'bg-amber-100 text-amber-700'
                         : 'bg-red-100 text-red-700'
                     }`}
                     title={
-                      engineHealth.engineConfigured
+                      engineHealth.engine_configured
                         ? 'Engine ready'
-                        : engineHealth.ollamaAvailable
+                        : engineHealth.ollama_available
                         ? 'Engine degraded - missing models'
                         : 'Engine not available'
                     }
                   >
-                    <span>{engineHealth.engineConfigured ? '✓' : engineHealth.ollamaAvailable ? '⚠' : '✗'}</span>
+                    <span>{engineHealth.engine_configured ? '✓' : engineHealth.ollama_available ? '⚠' : '✗'}</span>
                     <span>Engine</span>
                   </div>
                 )}
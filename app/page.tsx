@@ -22,7 +22,7 @@ export default function Home() {
       // Only check engine in desktop mode
       if (isDesktop()) {
         const health = await checkEngineHealth();
-        if (!health.engineConfigured || !health.ollamaAvailable) {
+        if (!health.engine_configured || !health.ollama_available) {
           setShowFirstLaunch(true);
         } else {
           setShowDesktop(true);
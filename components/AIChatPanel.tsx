@@ -145,8 +145,8 @@ Audience: ${projectData?.audienceType || 'SELF'}`;
         {
           role: tone === 'gossip' ? 'gossip' : 'analysis',
           question: userQuestion,
-          contextSummary: `${context}\n\n${contextSummary}`,
-          projectMeta: {
+          context_summary: `${context}\n\n${contextSummary}`,
+          project_meta: {
             name: projectData?.name || 'Project',
             audience: (projectData?.audienceType?.toLowerCase() || 'self') as 'self' | 'team' | 'exec',
           },
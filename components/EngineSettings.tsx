@@ -1,7 +1,7 @@
 'use client';
 
 import { useState, useEffect } from 'react';
-import { checkEngineHealth, installPack } from '@/lib/engine-client';
+import { checkEngineHealth, getDiagnostics, installPack } from '@/lib/engine-client';
 import type { EngineHealth } from '@/lib/engine-config';
 import { DEFAULT_CONFIG } from '@/lib/engine-config';
 
@@ -15,6 +15,8 @@ export default function EngineSettings({ onClose }: EngineSettingsProps) {
   const [selectedPack, setSelectedPack] = useState<string>('');
   const [testing, setTesting] = useState(false);
   const [testResult, setTestResult] = useState<string>('');
+  const [copyingReport, setCopyingReport] = useState(false);
+  const [reportStatus, setReportStatus] = useState<string>('');
 
   useEffect(() => {
     loadHealth();
@@ -24,7 +26,7 @@ export default function EngineSettings({ onClose }: EngineSettingsProps) {
     try {
       const healthStatus = await checkEngineHealth();
       setHealth(healthStatus);
-      setSelectedPack(healthStatus.activePackId || 'analyst_fast');
+      setSelectedPack(healthStatus.active_pack_id || 'analyst_fast');
     } catch (error: any) {
       console.error('Failed to load engine health:', error);
     } finally {
@@ -33,11 +35,11 @@ export default function EngineSettings({ onClose }: EngineSettingsProps) {
   };
 
   const handleRepairPack = async () => {
-    if (!health?.activePackId) return;
+    if (!health?.active_pack_id) return;
 
     setLoading(true);
     try {
-      const updatedHealth = await installPack(health.activePackId);
+      const updatedHealth = await installPack(health.active_pack_id);
       setHealth(updatedHealth);
     } catch (error: any) {
       console.error('Failed to repair pack:', error);
@@ -72,8 +74,8 @@ export default function EngineSettings({ onClose }: EngineSettingsProps) {
         {
           role: 'analysis',
           question: 'Say "ready" once.',
-          contextSummary: 'Test',
-          projectMeta: { name: 'Test', audience: 'self' },
+          context_summary: 'Test',
+          project_meta: { name: 'Test', audience: 'self' },
         },
         (chunk) => {
           setTestResult((prev) => prev + chunk);
@@ -89,6 +91,21 @@ export default function EngineSettings({ onClose }: EngineSettingsProps) {
     }
   };
 
+  const handleCopyDiagnostics = async () => {
+    setCopyingReport(true);
+    setReportStatus('');
+
+    try {
+      const diagnostics = await getDiagnostics();
+      await navigator.clipboard.writeText(diagnostics.report);
+      setReportStatus('Diagnostics copied to the clipboard');
+    } catch (error: any) {
+      setReportStatus(`Error: ${error.message}`);
+    } finally {
+      setCopyingReport(false);
+    }
+  };
+
   if (loading && !health) {
     return (
       <div className="p-6">
@@ -97,15 +114,15 @@ export default function EngineSettings({ onClose }: EngineSettingsProps) {
     );
   }
 
-  const statusIcon = health?.engineConfigured
+  const statusIcon = health?.engine_configured
     ? '✅'
-    : health?.ollamaAvailable
+    : health?.ollama_available
     ? '⚠️'
     : '❌';
 
-  const statusText = health?.engineConfigured
+  const statusText = health?.engine_configured
     ? 'All good'
-    : health?.ollamaAvailable
+    : health?.ollama_available
     ? 'Degraded'
     : 'Not connected';
 
@@ -132,13 +149,13 @@ export default function EngineSettings({ onClose }: EngineSettingsProps) {
         </div>
         {health && (
           <div className="text-xs text-slate-600 space-y-1">
-            <div>Ollama: {health.ollamaAvailable ? 'Available' : 'Not available'}</div>
-            {health.activePackId && (
-              <div>Active Pack: {DEFAULT_CONFIG.packs[health.activePackId]?.label || health.activePackId}</div>
+            <div>Ollama: {health.ollama_available ? 'Available' : 'Not available'}</div>
+            {health.active_pack_id && (
+              <div>Active Pack: {DEFAULT_CONFIG.packs[health.active_pack_id]?.label || health.active_pack_id}</div>
             )}
-            {health.missingModels && health.missingModels.length > 0 && (
+            {health.missing_models && health.missing_models.length > 0 && (
               <div className="text-amber-600">
-                Missing: {health.missingModels.join(', ')}
+                Missing: {health.missing_models.join(', ')}
               </div>
             )}
           </div>
@@ -162,12 +179,12 @@ export default function EngineSettings({ onClose }: EngineSettingsProps) {
         <div className="flex space-x-2">
           <button
             onClick={handleSwitchPack}
-            disabled={loading || selectedPack === health?.activePackId}
+            disabled={loading || selectedPack === health?.active_pack_id}
             className="px-3 py-1.5 text-sm bg-slate-900 text-white rounded hover:bg-slate-800 disabled:opacity-50 transition-colors"
           >
             Switch Pack
           </button>
-          {health?.missingModels && health.missingModels.length > 0 && (
+          {health?.missing_models && health.missing_models.length > 0 && (
             <button
               onClick={handleRepairPack}
               disabled={loading}
@@ -184,11 +201,21 @@ export default function EngineSettings({ onClose }: EngineSettingsProps) {
         <h3 className="text-sm font-medium text-slate-700 mb-2">Diagnostics</h3>
         <button
           onClick={handleQuickTest}
-          disabled={testing || !health?.engineConfigured}
+          disabled={testing || !health?.engine_configured}
           className="px-3 py-1.5 text-sm bg-slate-200 text-slate-700 rounded hover:bg-slate-300 disabled:opacity-50 transition-colors"
         >
           {testing ? 'Testing...' : 'Run Quick Test'}
         </button>
+        <button
+          onClick={handleCopyDiagnostics}
+          disabled={copyingReport}
+          className="ml-2 px-3 py-1.5 text-sm bg-slate-200 text-slate-700 rounded hover:bg-slate-300 disabled:opacity-50 transition-colors"
+        >
+          {copyingReport ? 'Collecting...' : 'Copy Diagnostics'}
+        </button>
+        {reportStatus && (
+          <div className="mt-2 text-xs text-slate-600">{reportStatus}</div>
+        )}
         {testResult && (
           <div className="mt-2 p-3 bg-slate-50 rounded text-xs text-slate-700 whitespace-pre-wrap">
             {testResult}
@@ -197,12 +224,16 @@ export default function EngineSettings({ onClose }: EngineSettingsProps) {
       </div>
 
       {/* GPU Info */}
-      {health?.gpuSummary && (
+      {health?.gpu_summary && (
         <div className="text-xs text-slate-600">
-          <div>GPU: {health.gpuSummary.vendor}</div>
-          {health.gpuSummary.vramGb && (
-            <div>VRAM: {health.gpuSummary.vramGb}GB</div>
-          )}
+          <div>GPU: {health.gpu_summary.description}</div>
+          {health.gpu_summary.gpus.map((gpu, i) => (
+            <div key={i}>
+              {gpu.name ?? gpu.vendor}
+              {gpu.vram_mb !== null && ` · ${Math.round(gpu.vram_mb / 1024)}GB VRAM`}
+              {gpu.driver && ` · ${gpu.driver}${gpu.driver_version ? ` ${gpu.driver_version}` : ''}`}
+            </div>
+          ))}
         </div>
       )}
     </div>
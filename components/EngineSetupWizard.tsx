@@ -29,9 +29,9 @@ export default function EngineSetupWizard({ onComplete, onSkip }: EngineSetupWiz
       const healthStatus = await checkEngineHealth();
       setHealth(healthStatus);
 
-      if (!healthStatus.ollamaAvailable) {
+      if (!healthStatus.ollama_available) {
         setStep('ollama-missing');
-      } else if (!healthStatus.engineConfigured || healthStatus.missingModels.length > 0) {
+      } else if (!healthStatus.engine_configured || healthStatus.missing_models.length > 0) {
         setStep('pack-selection');
       } else {
         // Already configured
@@ -147,9 +147,9 @@ export default function EngineSetupWizard({ onComplete, onSkip }: EngineSetupWiz
             </p>
           </div>
 
-          {health?.missingModels && health.missingModels.length > 0 && (
+          {health?.missing_models && health.missing_models.length > 0 && (
             <div className="mb-4 bg-amber-50 border border-amber-200 text-amber-700 px-4 py-3 rounded text-sm">
-              Missing models: {health.missingModels.join(', ')}
+              Missing models: {health.missing_models.join(', ')}
             </div>
           )}
 
@@ -166,7 +166,7 @@ export default function EngineSetupWizard({ onComplete, onSkip }: EngineSetupWiz
               >
                 <div className="font-medium text-slate-900">{pack.label}</div>
                 <div className="text-xs text-slate-600 mt-1">
-                  Analysis: {pack.analysisModel} • Report: {pack.reportModel}
+                  Analysis: {pack.analysis_model} • Report: {pack.report_model}
                 </div>
               </button>
             ))}
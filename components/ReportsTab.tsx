@@ -235,9 +235,9 @@ export default function ReportsTab({ projectId, onUpdate }: { projectId: string;
         // Use engine to generate report section
         const { generateReport } = await import('@/lib/engine-client');
         const response = await generateReport({
-          templateType: selectedReport.templateType || 'general',
+          template_type: selectedReport.templateType || 'general',
           audience: (project.audienceType?.toLowerCase() || 'self') as 'self' | 'team' | 'exec',
-          dataSummary: dataSummaries.join('\n\n'),
+          data_summary: dataSummaries.join('\n\n'),
         });
 
         // Parse the markdown to extract the section content
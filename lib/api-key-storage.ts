@@ -1,24 +1,47 @@
 // API Key storage utilities for desktop app
-// Uses Tauri commands to access Windows Credential Manager
+// Uses Tauri commands to access the OS credential store
 
-export async function getApiKey(): Promise<string | null> {
+export interface ApiKeyInfo {
+  credential_id: string;
+  masked_key: string;
+  key_source: 'environment' | 'file' | 'secret_store';
+  created_at: number | null;
+  stored_at: number | null;
+  last_used_at: number | null;
+  last_validated_at: number | null;
+  expires_at: number | null;
+}
+
+// The key itself never leaves the Rust side; only a masked preview and metadata do
+export async function getApiKeyInfo(credentialId?: string): Promise<ApiKeyInfo | null> {
   // Check if running in Tauri
   if (typeof window === 'undefined' || !(window as any).__TAURI__) {
-    // Web mode - fallback to environment variable
-    return process.env.ANTHROPIC_API_KEY || null;
+    // Web mode - describe the environment variable
+    const key = process.env.ANTHROPIC_API_KEY;
+    return key
+      ? {
+          credential_id: 'default',
+          masked_key: `••••${key.slice(-4)}`,
+          key_source: 'environment',
+          created_at: null,
+          stored_at: null,
+          last_used_at: null,
+          last_validated_at: null,
+          expires_at: null,
+        }
+      : null;
   }
 
   try {
     const { invoke } = await import('@tauri-apps/api/tauri');
-    const key = await invoke<string>('get_api_key');
-    return key;
+    return await invoke<ApiKeyInfo>('get_api_key_info', { credentialId });
   } catch (error) {
-    console.error('Failed to get API key:', error);
+    console.error('Failed to get API key info:', error);
     return null;
   }
 }
 
-export async function storeApiKey(key: string): Promise<void> {
+export async function storeApiKey(key: string, credentialId?: string): Promise<void> {
   // Check if running in Tauri
   if (typeof window === 'undefined' || !(window as any).__TAURI__) {
     // Web mode - can't store securely, just return
@@ -28,14 +51,14 @@ export async function storeApiKey(key: string): Promise<void> {
 
   try {
     const { invoke } = await import('@tauri-apps/api/tauri');
-    await invoke('store_api_key', { key });
+    await invoke('store_api_key', { key, credentialId });
   } catch (error) {
     console.error('Failed to store API key:', error);
     throw new Error('Failed to store API key securely');
   }
 }
 
-export async function hasApiKey(): Promise<boolean> {
+export async function hasApiKey(credentialId?: string): Promise<boolean> {
   // Check if running in Tauri
   if (typeof window === 'undefined' || !(window as any).__TAURI__) {
     // Web mode - check environment variable
@@ -44,14 +67,14 @@ export async function hasApiKey(): Promise<boolean> {
 
   try {
     const { invoke } = await import('@tauri-apps/api/tauri');
-    return await invoke<boolean>('has_api_key');
+    return await invoke<boolean>('has_api_key', { credentialId });
   } catch (error) {
     console.error('Failed to check API key:', error);
     return false;
   }
 }
 
-export async function deleteApiKey(): Promise<void> {
+export async function deleteApiKey(credentialId?: string): Promise<void> {
   // Check if running in Tauri
   if (typeof window === 'undefined' || !(window as any).__TAURI__) {
     // Web mode - nothing to delete
@@ -60,51 +83,82 @@ export async function deleteApiKey(): Promise<void> {
 
   try {
     const { invoke } = await import('@tauri-apps/api/tauri');
-    await invoke('delete_api_key');
+    await invoke('delete_api_key', { credentialId });
   } catch (error) {
     console.error('Failed to delete API key:', error);
     throw new Error('Failed to delete API key');
   }
 }
 
-export async function testApiKey(key: string): Promise<boolean> {
+export async function listApiKeys(): Promise<string[]> {
+  // Check if running in Tauri
+  if (typeof window === 'undefined' || !(window as any).__TAURI__) {
+    // Web mode - only the environment variable exists
+    return process.env.ANTHROPIC_API_KEY ? ['default'] : [];
+  }
+
   try {
-    if (!key || !key.trim()) {
-      throw new Error('API key cannot be empty');
-    }
-
-    const response = await fetch('https://api.anthropic.com/v1/messages', {
-      method: 'POST',
-      headers: {
-        'Content-Type': 'application/json',
-        'x-api-key': key,
-        'anthropic-version': '2023-06-01',
-      },
-      body: JSON.stringify({
-        model: 'claude-3-5-sonnet-20241022',
-        max_tokens: 10,
-        messages: [{ role: 'user', content: 'test' }],
-      }),
-    });
-
-    if (!response.ok) {
-      const errorData = await response.json().catch(() => ({}));
-      if (response.status === 401) {
-        throw new Error('Invalid API key. Please check your key and try again.');
-      } else if (response.status === 429) {
-        throw new Error('Rate limit exceeded. Please try again later.');
-      } else {
-        throw new Error(errorData.error?.message || `API request failed with status ${response.status}`);
-      }
-    }
-
-    return true;
-  } catch (error: any) {
-    console.error('API key test failed:', error);
-    if (error.message) {
-      throw error; // Re-throw with user-friendly message
-    }
-    throw new Error('Failed to connect to API. Please check your internet connection.');
+    const { invoke } = await import('@tauri-apps/api/tauri');
+    return await invoke<string[]>('list_api_keys');
+  } catch (error) {
+    console.error('Failed to list API keys:', error);
+    return [];
+  }
+}
+
+export type KeyValidationStatus =
+  | 'valid'
+  | 'invalid'
+  | 'rate_limited'
+  | 'network_unreachable'
+  | 'config_error' // unknown provider or bad base URL; no request was made
+  | 'unexpected'
+  | 'not_applicable'; // the provider takes no key, e.g. Ollama
+
+export interface KeyValidationResult {
+  status: KeyValidationStatus;
+  message: string;
+  http_status: number | null;
+  stored: boolean;
+}
+
+export interface KeyTarget {
+  credentialId?: string;
+  // Default to the configured provider and its base URL
+  provider?: string;
+  baseUrl?: string;
+}
+
+// The check runs on the Rust side with a free models-list request; pass `store` to keep a valid key
+export async function validateApiKey(
+  key: string,
+  target: KeyTarget = {},
+  store = false
+): Promise<KeyValidationResult> {
+  if (!key || !key.trim()) {
+    throw new Error('API key cannot be empty');
+  }
+  if (typeof window === 'undefined' || !(window as any).__TAURI__) {
+    throw new Error('API key validation is only available in the desktop app');
   }
+
+  const { invoke } = await import('@tauri-apps/api/tauri');
+  return await invoke<KeyValidationResult>('validate_api_key', { key, ...target, store });
 }
 
+// Replaces a stored key only once the new one validates; the old key stays otherwise
+export async function rotateApiKey(
+  newKey: string,
+  target: KeyTarget = {},
+  expiresAt?: number
+): Promise<KeyValidationResult> {
+  if (!newKey || !newKey.trim()) {
+    throw new Error('API key cannot be empty');
+  }
+  if (typeof window === 'undefined' || !(window as any).__TAURI__) {
+    throw new Error('API key rotation is only available in the desktop app');
+  }
+
+  const { invoke } = await import('@tauri-apps/api/tauri');
+  return await invoke<KeyValidationResult>('rotate_api_key', { newKey, ...target, expiresAt });
+}
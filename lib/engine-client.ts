@@ -4,8 +4,21 @@
 import { invoke } from '@tauri-apps/api/tauri';
 import { listen } from '@tauri-apps/api/event';
 import type {
+  EngineConfigChangedEvent,
+  EngineConfigRejectedEvent,
+  ManagedConfig,
+  ImportConflictStrategy,
+  PackImportPreview,
+  PackImportResult,
+  ModelPack,
+  PackSummary,
   EngineHealth,
+  EngineDiagnostics,
+  PackRecommendations,
   EngineChatRequest,
+  EngineChatResponse,
+  EngineChatChunk,
+  EngineChatDone,
   EngineReportRequest,
   EngineReportResponse,
 } from './engine-config';
@@ -21,6 +34,63 @@ export async function checkEngineHealth(): Promise<EngineHealth> {
   }
 }
 
+/**
+ * Get the engine config currently in effect, with the fields the organization policy locks
+ */
+export async function getEngineConfig(): Promise<ManagedConfig> {
+  try {
+    return await invoke<ManagedConfig>('engine_get_config');
+  } catch (error: any) {
+    throw new Error(error || 'Failed to get engine config');
+  }
+}
+
+/**
+ * Listen for engine config changes, including edits to engine-config.json made outside the app
+ * Returns a function that stops listening
+ */
+export async function onEngineConfigChanged(
+  handler: (event: EngineConfigChangedEvent) => void
+): Promise<() => void> {
+  return listen<EngineConfigChangedEvent>('engine_config_changed', (event) => {
+    handler(event.payload);
+  });
+}
+
+/**
+ * Listen for outside edits to engine-config.json that were rejected as invalid
+ * The previous config stays in effect
+ */
+export async function onEngineConfigRejected(
+  handler: (event: EngineConfigRejectedEvent) => void
+): Promise<() => void> {
+  return listen<EngineConfigRejectedEvent>('engine_config_rejected', (event) => {
+    handler(event.payload);
+  });
+}
+
+/**
+ * Collect a diagnostics report for support, with secrets redacted
+ */
+export async function getDiagnostics(): Promise<EngineDiagnostics> {
+  try {
+    return await invoke<EngineDiagnostics>('engine_diagnostics');
+  } catch (error: any) {
+    throw new Error(error || 'Failed to collect diagnostics');
+  }
+}
+
+/**
+ * Score each pack against this machine's RAM, CPU, disk and GPU, best first
+ */
+export async function recommendPack(): Promise<PackRecommendations> {
+  try {
+    return await invoke<PackRecommendations>('engine_recommend_pack');
+  } catch (error: any) {
+    throw new Error(error || 'Failed to recommend a pack');
+  }
+}
+
 /**
  * Install a model pack
  */
@@ -32,33 +102,159 @@ export async function installPack(packId: string): Promise<EngineHealth> {
   }
 }
 
+/**
+ * List all model packs in the engine config
+ */
+export async function listPacks(): Promise<PackSummary[]> {
+  try {
+    return await invoke<PackSummary[]>('engine_list_packs');
+  } catch (error: any) {
+    throw new Error(error || 'Failed to list packs');
+  }
+}
+
+/**
+ * Create a user-defined model pack
+ */
+export async function createPack(packId: string, pack: ModelPack): Promise<PackSummary[]> {
+  try {
+    return await invoke<PackSummary[]>('engine_create_pack', { packId, pack });
+  } catch (error: any) {
+    throw new Error(error || 'Failed to create pack');
+  }
+}
+
+/**
+ * Update a user-defined model pack
+ */
+export async function updatePack(packId: string, pack: ModelPack): Promise<PackSummary[]> {
+  try {
+    return await invoke<PackSummary[]>('engine_update_pack', { packId, pack });
+  } catch (error: any) {
+    throw new Error(error || 'Failed to update pack');
+  }
+}
+
+/**
+ * Delete a user-defined model pack
+ */
+export async function deletePack(packId: string): Promise<PackSummary[]> {
+  try {
+    return await invoke<PackSummary[]>('engine_delete_pack', { packId });
+  } catch (error: any) {
+    throw new Error(error || 'Failed to delete pack');
+  }
+}
+
+/**
+ * Keep your own copy of a built-in pack instead of following the shipped version
+ */
+export async function pinPack(packId: string): Promise<PackSummary[]> {
+  try {
+    return await invoke<PackSummary[]>('engine_pin_pack', { packId });
+  } catch (error: any) {
+    throw new Error(error || 'Failed to pin pack');
+  }
+}
+
+/**
+ * Go back to the shipped version of a built-in pack
+ */
+export async function unpinPack(packId: string): Promise<PackSummary[]> {
+  try {
+    return await invoke<PackSummary[]>('engine_unpin_pack', { packId });
+  } catch (error: any) {
+    throw new Error(error || 'Failed to unpin pack');
+  }
+}
+
+/**
+ * Make a pack active without installing its models
+ */
+export async function setActivePack(packId: string): Promise<PackSummary[]> {
+  try {
+    return await invoke<PackSummary[]>('engine_set_active_pack', { packId });
+  } catch (error: any) {
+    throw new Error(error || 'Failed to set active pack');
+  }
+}
+
+/**
+ * Export packs to a standalone JSON file that can be shared and imported elsewhere
+ */
+export async function exportPacks(packIds: string[], path: string): Promise<void> {
+  try {
+    await invoke('engine_export_packs', { packIds, path });
+  } catch (error: any) {
+    throw new Error(error || 'Failed to export packs');
+  }
+}
+
+/**
+ * Preview a pack file: its packs, id conflicts and the models it would pull
+ */
+export async function previewPackImport(path: string): Promise<PackImportPreview> {
+  try {
+    return await invoke<PackImportPreview>('engine_preview_pack_import', { path });
+  } catch (error: any) {
+    throw new Error(error || 'Failed to read pack file');
+  }
+}
+
+/**
+ * Import packs from a file, optionally only some of them
+ */
+export async function importPacks(
+  path: string,
+  onConflict: ImportConflictStrategy,
+  packIds?: string[]
+): Promise<PackImportResult> {
+  try {
+    return await invoke<PackImportResult>('engine_import_packs', { path, onConflict, packIds });
+  } catch (error: any) {
+    throw new Error(error || 'Failed to import packs');
+  }
+}
+
 /**
  * Chat with the engine (streaming)
  * Returns the full response and sets up event listeners for chunks
+ * onDone receives the provider that answered, which may be a fallback
  */
 export async function chat(
   request: EngineChatRequest,
   onChunk?: (chunk: string) => void,
-  onDone?: () => void
+  onDone?: (done: EngineChatDone) => void
 ): Promise<string> {
+  return (await chatWithProvider(request, onChunk, onDone)).content;
+}
+
+/**
+ * Like chat, but resolves to the full response including which provider answered
+ */
+export async function chatWithProvider(
+  request: EngineChatRequest,
+  onChunk?: (chunk: string) => void,
+  onDone?: (done: EngineChatDone) => void
+): Promise<EngineChatResponse> {
   try {
     // Set up event listeners before calling
     const chunkUnlisten = onChunk
-      ? await listen<string>('engine_chat_chunk', (event) => {
-          onChunk(event.payload);
+      ? await listen<EngineChatChunk>('engine_chat_chunk', (event) => {
+          onChunk(event.payload.content);
         })
       : null;
 
     const doneUnlisten = onDone
-      ? await listen('engine_chat_done', () => {
-          onDone();
+      ? await listen<EngineChatDone>('engine_chat_done', (event) => {
+          onDone(event.payload);
           chunkUnlisten?.();
           doneUnlisten?.();
         })
       : null;
 
     // Call the command
-    const result = await invoke<string>('engine_chat', { request });
+    const result = await invoke<EngineChatResponse>('engine_chat', { request });
 
     // Clean up listeners if not already done
     if (!onDone) {
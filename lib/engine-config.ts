@@ -1,68 +1,267 @@
 // Engine configuration types matching Rust structs
+// Field names are snake_case, exactly as serde writes them and as engine-config.json stores them
+
+export interface GenerationSettings {
+  temperature?: number | null;
+  top_p?: number | null;
+  top_k?: number | null;
+  max_tokens?: number | null;
+  num_ctx?: number | null;
+  stop?: string[];
+  seed?: number | null;
+}
 
 export interface ModelPack {
   label: string;
-  analysisModel: string;
-  reportModel: string;
-  embeddingModel: string;
+  analysis_model: string;
+  report_model: string;
+  embedding_model: string;
+  gossip_model?: string | null;
+  analysis_settings?: GenerationSettings;
+  gossip_settings?: GenerationSettings;
+  report_settings?: GenerationSettings;
+  // Provider ids from the chain ("primary" or a fallback id); unset uses the primary.
+  // gossip_provider falls back to analysis_provider.
+  analysis_provider?: string | null;
+  gossip_provider?: string | null;
+  report_provider?: string | null;
+  embedding_provider?: string | null;
+}
+
+export interface PackSummary {
+  id: string;
+  pack: ModelPack;
+  builtin: boolean;
+  pinned: boolean;
+  differs_from_shipped: boolean;
+  active: boolean;
+  approved: boolean;
+  locked: boolean;
+}
+
+export interface ProviderConfig {
+  id: string;
+  provider: string; // "ollama" | "anthropic" | "openai_compatible"
+  base_url: string;
+  credential_id?: string | null;
+  // Model used on this provider in place of the pack's
+  model?: string | null;
 }
 
 export interface EngineConfig {
+  schema_version?: number;
   provider: string;
-  baseUrl: string;
-  activePackId: string | null;
+  base_url: string;
+  credential_id?: string | null;
+  api_key_env?: string | null;
+  api_key_file?: string | null;
+  active_pack_id: string | null;
   packs: Record<string, ModelPack>;
+  pinned_packs?: string[];
+  // Tried in order when the primary provider cannot answer
+  fallback_providers?: ProviderConfig[];
+}
+
+export type PackConflict = "identical" | "modified" | "builtin";
+
+export type ImportConflictStrategy = "skip" | "replace" | "rename";
+
+export interface PackImportItem {
+  id: string;
+  pack: ModelPack;
+  conflict: PackConflict | null;
+  models_to_pull: string[];
+  // Provider ids the pack routes to that are not configured here; import sends those roles
+  // to the primary provider, and `pack` already shows that
+  unresolved_routes: string[];
+}
+
+export interface PackImportPreview {
+  packs: PackImportItem[];
+  models_to_pull: string[];
+  // false when Ollama could not be reached, so every required model is listed
+  installed_models_known: boolean;
+}
+
+export interface PackImportResult {
+  imported: { from_id: string; id: string; cleared_routes: string[] }[];
+  skipped: string[];
+  packs: PackSummary[];
+}
+
+// Organization-managed policy; unset fields leave the setting to the user
+export interface EnginePolicy {
+  allowed_providers?: string[] | null;
+  allowed_base_url_hosts?: string[] | null;
+  approved_packs?: string[] | null;
+  allow_cloud_providers?: boolean | null;
+}
+
+export type LockedField = "provider" | "base_url" | "packs" | "active_pack_id";
+
+export interface ManagedConfig {
+  config: EngineConfig;
+  policy: EnginePolicy | null;
+  locked_fields: LockedField[];
+}
+
+export interface EngineConfigChangedEvent {
+  // "app" when a command changed it, "file" for an edit made outside the app
+  source: "app" | "file";
+  config: EngineConfig;
+  locked_fields: LockedField[];
+}
+
+export interface EngineConfigRejectedEvent {
+  error: string;
+}
+
+export interface GpuInfo {
+  vendor: "nvidia" | "amd" | "intel" | "apple" | "unknown";
+  name: string | null;
+  vram_mb: number | null;
+  driver: string | null;
+  driver_version: string | null;
+}
+
+export interface GpuSummary {
+  // Vendor of the GPU with the most VRAM
+  vendor: GpuInfo["vendor"] | "none";
+  vram_gb: number | null;
+  gpus: GpuInfo[];
+  cpu_only: boolean;
+  description: string; // e.g. "NVIDIA GeForce RTX 3090 (24 GB)" or "CPU only"
+}
+
+export interface HardwareInfo {
+  total_ram_gb: number | null;
+  available_ram_gb: number | null;
+  cpu_arch: string;
+  cpu_cores: number;
+  cpu_features: string[]; // e.g. "avx2", "neon"
+  models_dir: string | null;
+  free_disk_gb: number | null;
+  gpu: GpuSummary;
+}
+
+export interface PackRecommendation {
+  pack_id: string;
+  label: string;
+  recommended: boolean;
+  score: number; // 0-100, for ordering only
+  memory_needed_gb: number | null;
+  download_gb: number | null;
+  reasons: string[];
+}
+
+export interface PackRecommendations {
+  hardware: HardwareInfo;
+  packs: PackRecommendation[]; // best first
+  recommended_pack_id: string | null;
 }
 
 export interface EngineHealth {
-  ollamaAvailable: boolean;
-  engineConfigured: boolean;
-  activePackId: string | null;
-  missingModels: string[];
-  gpuSummary?: {
-    vendor: "nvidia" | "amd" | "intel" | "none";
-    vramGb: number | null;
-  };
+  ollama_available: boolean;
+  engine_configured: boolean;
+  active_pack_id: string | null;
+  missing_models: string[];
+  gpu_summary?: GpuSummary;
+  credential_alerts?: {
+    credential_id: string;
+    kind: "expired" | "expires_soon" | "rotation_due";
+    message: string;
+  }[];
+  config_recovery?: string | null;
+  policy_error?: string | null;
+  provider_error?: string | null; // unknown provider type or failed health check
+  provider?: ProviderConfig | null; // the provider the check ran against
+  ollama_version?: string | null;
+  latency_ms?: number | null;
+  loaded_models?: LoadedModel[];
+  models_dir?: string | null; // only for a provider on this machine
+  free_disk_gb?: number | null;
+  config_path?: string | null;
+}
+
+export interface LoadedModel {
+  name: string;
+  size_bytes: number | null;
+  vram_bytes: number | null;
+}
+
+export interface ProviderStatus {
+  provider: ProviderConfig;
+  available: boolean;
+  latency_ms: number | null;
+  error: string | null;
+}
+
+export interface EngineDiagnostics {
+  report: string; // plain text with secrets redacted, ready to copy
+  health: EngineHealth;
+  hardware: HardwareInfo;
+  fallback_providers: ProviderStatus[];
 }
 
 export interface EngineChatRequest {
   role: "analysis" | "gossip";
   question: string;
-  contextSummary: string;
-  projectMeta: {
+  context_summary: string;
+  project_meta: {
     name: string;
     audience: "self" | "team" | "exec";
   };
 }
 
 export interface EngineReportRequest {
-  templateType: "executive_summary" | "sales_overview" | "market_snapshot" | "general";
+  template_type: "executive_summary" | "sales_overview" | "market_snapshot" | "general";
   audience: "self" | "team" | "exec";
-  dataSummary: string;
+  data_summary: string;
 }
 
 export interface EngineReportResponse {
   markdown: string;
-  modelName: string;
+  model_name: string;
+  // "primary" or the id of the fallback provider that answered
+  provider_id: string;
+  provider: string;
+}
+
+export interface EngineChatResponse {
+  content: string;
+  model_name: string;
+  provider_id: string;
+  provider: string;
+}
+
+export interface EngineChatChunk {
+  content: string;
+  provider_id: string;
+}
+
+export interface EngineChatDone {
+  model_name: string;
+  provider_id: string;
+  provider: string;
 }
 
 // Default config structure
 export const DEFAULT_CONFIG: EngineConfig = {
   provider: "ollama",
-  baseUrl: "http://127.0.0.1:11434",
-  activePackId: "analyst_fast",
+  base_url: "http://127.0.0.1:11434",
+  active_pack_id: "analyst_fast",
   packs: {
     light_fast: {
       label: "Fast & Light",
-      analysisModel: "qwen3:4b",
-      reportModel: "qwen3:4b",
-      embeddingModel: "qwen3-embedding:4b",
+      analysis_model: "qwen3:4b",
+      report_model: "qwen3:4b",
+      embedding_model: "qwen3-embedding:4b",
     },
     analyst_fast: {
       label: "Analyst Pack (Recommended)",
-      analysisModel: "gurubot/glm-4.6v-flash-gguf:q4_k_m",
-      reportModel: "gurubot/glm-4.6v-flash-gguf:q4_k_m",
-      embeddingModel: "qwen3-embedding:4b",
+      analysis_model: "gurubot/glm-4.6v-flash-gguf:q4_k_m",
+      report_model: "gurubot/glm-4.6v-flash-gguf:q4_k_m",
+      embedding_model: "qwen3-embedding:4b",
     },
   },
 };
@@ -412,38 +412,33 @@ Generate a confession and talking points.`,
 }
 
 class AnthropicClient implements LLMClient {
-  private apiKey: string | null = null;
-  private apiKeyPromise: Promise<string> | null = null;
-
-  private async getApiKey(): Promise<string> {
-    if (this.apiKey) {
-      return this.apiKey;
+  // Desktop: the Rust side injects the stored key so it never reaches the webview
+  // Web: call the API directly with the environment key
+  private async postMessages(body: unknown): Promise<Response> {
+    if (typeof window !== 'undefined' && (window as any).__TAURI__) {
+      const { invoke } = await import('@tauri-apps/api/tauri');
+      try {
+        const data = await invoke('anthropic_messages', { body });
+        return new Response(JSON.stringify(data), { status: 200 });
+      } catch (error: any) {
+        return new Response(JSON.stringify({ error: { message: String(error) } }), { status: 502 });
+      }
     }
 
-    if (this.apiKeyPromise) {
-      return this.apiKeyPromise;
+    const apiKey = process.env.ANTHROPIC_API_KEY;
+    if (!apiKey) {
+      throw new Error('ANTHROPIC_API_KEY is not set. Please configure your API key in settings.');
     }
 
-    this.apiKeyPromise = (async () => {
-      // Try to get from secure storage (desktop) or env var (web)
-      const { getApiKey } = await import('./api-key-storage');
-      const key = await getApiKey();
-
-      if (!key) {
-        // Fallback to environment variable for web mode
-        const envKey = process.env.ANTHROPIC_API_KEY;
-        if (!envKey) {
-          throw new Error('ANTHROPIC_API_KEY is not set. Please configure your API key in settings.');
-        }
-        this.apiKey = envKey;
-        return envKey;
-      }
-
-      this.apiKey = key;
-      return key;
-    })();
-
-    return this.apiKeyPromise;
+    return fetch('https://api.anthropic.com/v1/messages', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        'x-api-key': apiKey,
+        'anthropic-version': '2023-06-01',
+      },
+      body: JSON.stringify(body),
+    });
   }
 
   async generateReportSections(
@@ -451,8 +446,6 @@ class AnthropicClient implements LLMClient {
     dataSummaries: string,
     audience: string
   ): Promise<string> {
-    const apiKey = await this.getApiKey();
-
     const audienceGuidance: Record<string, string> = {
       'individual contributor': 'Write for someone who works directly with the data. Use technical terms when appropriate, include detailed numbers, and provide actionable next steps.',
       'manager or director': 'Write for decision-makers who need strategic insights. Focus on trends, implications, and recommendations. Use clear metrics and avoid jargon.',
@@ -461,17 +454,10 @@ class AnthropicClient implements LLMClient {
 
     const guidance = audienceGuidance[audience] || 'Write clearly and professionally for a business audience.';
 
-    const response = await fetch('https://api.anthropic.com/v1/messages', {
-      method: 'POST',
-      headers: {
-        'Content-Type': 'application/json',
-        'x-api-key': apiKey,
-        'anthropic-version': '2023-06-01',
-      },
-      body: JSON.stringify({
-        model: 'claude-3-5-sonnet-20241022',
-        max_tokens: 4096,
-        system: `You are a professional data analyst creating business-ready reports.${toneGuidance}
+    const response = await this.postMessages({
+      model: 'claude-3-5-sonnet-20241022',
+      max_tokens: 4096,
+      system: `You are a professional data analyst creating business-ready reports.${toneGuidance}
 
 Target Audience: ${audience}
 ${guidance}
@@ -518,19 +504,18 @@ IMPORTANT:
 - If data is missing, note it as a limitation
 - Reference specific charts or tables when available
 - Use consistent terminology throughout`,
-        messages: [
-          {
-            role: 'user',
-            content: `Report Template Structure:
+      messages: [
+        {
+          role: 'user',
+          content: `Report Template Structure:
 ${template}
 
 Available Data:
 ${dataSummaries}
 
 Generate the complete report following the template structure and formatting requirements above.`,
-          },
-        ],
-      }),
+        },
+      ],
     });
 
     const data = await response.json();
@@ -546,20 +531,11 @@ Generate the complete report following the template structure and formatting req
     context: string,
     dataSummaries: string
   ): Promise<{ answer: string; supportingData: any; caveats: string[] }> {
-    const apiKey = await this.getApiKey();
-
-    const response = await fetch('https://api.anthropic.com/v1/messages', {
-      method: 'POST',
-      headers: {
-        'Content-Type': 'application/json',
-        'x-api-key': apiKey,
-        'anthropic-version': '2023-06-01',
-      },
-      body: JSON.stringify({
-        model: 'claude-3-5-sonnet-20241022',
-        max_tokens: 4096,
-        system: tone === 'gossip'
-          ? `You are a data analyst with a conversational, slightly playful tone. Answer questions based on the provided data in a casual but accurate way.
+    const response = await this.postMessages({
+      model: 'claude-3-5-sonnet-20241022',
+      max_tokens: 4096,
+      system: tone === 'gossip'
+        ? `You are a data analyst with a conversational, slightly playful tone. Answer questions based on the provided data in a casual but accurate way.
         Always structure your response as:
 
         DIRECT ANSWER:
@@ -572,7 +548,7 @@ Generate the complete report following the template structure and formatting req
         - [List any data gaps or uncertainties]
 
         Quote exact figures where possible. Keep it accurate but friendly.`
-          : `You are a data analyst. Answer questions based on the provided data.
+        : `You are a data analyst. Answer questions based on the provided data.
         Always structure your response as:
 
         DIRECT ANSWER:
@@ -585,13 +561,12 @@ Generate the complete report following the template structure and formatting req
         - [List any data gaps or uncertainties]
 
         Quote exact figures where possible.`,
-        messages: [
-          {
-            role: 'user',
-            content: `Project Context:\n${context}\n\nAvailable Data:\n${dataSummaries}\n\nQuestion: ${question}${tone === 'gossip' ? '\n\nUse a conversational, slightly playful tone while staying accurate.' : ''}`,
-          },
-        ],
-      }),
+      messages: [
+        {
+          role: 'user',
+          content: `Project Context:\n${context}\n\nAvailable Data:\n${dataSummaries}\n\nQuestion: ${question}${tone === 'gossip' ? '\n\nUse a conversational, slightly playful tone while staying accurate.' : ''}`,
+        },
+      ],
     });
 
     const data = await response.json();
@@ -614,26 +589,16 @@ Generate the complete report following the template structure and formatting req
   }
 
   async summarizeTable(tableSummary: string): Promise<string> {
-    const apiKey = await this.getApiKey();
-
-    const response = await fetch('https://api.anthropic.com/v1/messages', {
-      method: 'POST',
-      headers: {
-        'Content-Type': 'application/json',
-        'x-api-key': apiKey,
-        'anthropic-version': '2023-06-01',
-      },
-      body: JSON.stringify({
-        model: 'claude-3-5-sonnet-20241022',
-        max_tokens: 1024,
-        system: 'You are a data analyst. Provide a concise summary of the table data.',
-        messages: [
-          {
-            role: 'user',
-            content: `Summarize this table:\n${tableSummary}`,
-          },
-        ],
-      }),
+    const response = await this.postMessages({
+      model: 'claude-3-5-sonnet-20241022',
+      max_tokens: 1024,
+      system: 'You are a data analyst. Provide a concise summary of the table data.',
+      messages: [
+        {
+          role: 'user',
+          content: `Summarize this table:\n${tableSummary}`,
+        },
+      ],
     });
 
     const data = await response.json();
@@ -645,19 +610,10 @@ Generate the complete report following the template structure and formatting req
   }
 
   async describeChart(chartConfig: any, sampleData: any): Promise<{ title: string; insight: string }> {
-    const apiKey = await this.getApiKey();
-
-    const response = await fetch('https://api.anthropic.com/v1/messages', {
-      method: 'POST',
-      headers: {
-        'Content-Type': 'application/json',
-        'x-api-key': apiKey,
-        'anthropic-version': '2023-06-01',
-      },
-      body: JSON.stringify({
-        model: 'claude-3-5-sonnet-20241022',
-        max_tokens: 512,
-        system: `You are a professional data analyst creating business-ready visualizations.
+    const response = await this.postMessages({
+      model: 'claude-3-5-sonnet-20241022',
+      max_tokens: 512,
+      system: `You are a professional data analyst creating business-ready visualizations.
 
 For chart titles:
 - Be specific and descriptive (include metric name, dimension, and time period if applicable)
@@ -680,10 +636,10 @@ Example insights:
 - "Revenue increased 23% year-over-year, driven primarily by Q3 growth in the enterprise segment."
 - "The West region accounts for 42% of total sales, indicating strong market penetration."
 - "Conversion rates drop significantly at the proposal stage, suggesting pricing sensitivity."`,
-        messages: [
-          {
-            role: 'user',
-            content: `Chart Configuration:
+      messages: [
+        {
+          role: 'user',
+          content: `Chart Configuration:
 ${JSON.stringify(chartConfig, null, 2)}
 
 Sample Data (first 5 rows):
@@ -692,9 +648,8 @@ ${JSON.stringify(sampleData?.slice(0, 5) || [], null, 2)}
 Provide ONLY:
 TITLE: [Your title here]
 INSIGHT: [Your insight here]`,
-          },
-        ],
-      }),
+        },
+      ],
     });
 
     const data = await response.json();
@@ -718,8 +673,6 @@ INSIGHT: [Your insight here]`,
     project: any,
     audience: string
   ): Promise<{ confession: string; talkingPoints: string[] }> {
-    const apiKey = await this.getApiKey();
-
     // Build data summaries
     const dataSummaries: string[] = [];
     if (project.dataSources) {
@@ -744,17 +697,10 @@ Columns: ${table.columnProfiles?.map((cp: any) => `${cp.name} (${cp.dataType})`)
     };
     const audienceText = audienceMap[audience] || 'general audience';
 
-    const response = await fetch('https://api.anthropic.com/v1/messages', {
-      method: 'POST',
-      headers: {
-        'Content-Type': 'application/json',
-        'x-api-key': apiKey,
-        'anthropic-version': '2023-06-01',
-      },
-      body: JSON.stringify({
-        model: 'claude-3-5-sonnet-20241022',
-        max_tokens: 1024,
-        system: `You are a data analyst creating a quick "confession" - a clear, honest summary of what the data reveals.
+    const response = await this.postMessages({
+      model: 'claude-3-5-sonnet-20241022',
+      max_tokens: 1024,
+      system: `You are a data analyst creating a quick "confession" - a clear, honest summary of what the data reveals.
 
 Format your response EXACTLY as:
 CONFESSION:
@@ -767,10 +713,10 @@ TALKING POINTS:
 - [Bullet point 4 - what to say in the meeting]
 
 The confession should be conversational but professional. The talking points should be ready to use in a meeting.`,
-        messages: [
-          {
-            role: 'user',
-            content: `Question: ${question}
+      messages: [
+        {
+          role: 'user',
+          content: `Question: ${question}
 
 Available Data:
 ${dataSummaries.join('\n\n')}
@@ -778,9 +724,8 @@ ${dataSummaries.join('\n\n')}
 Audience: ${audienceText}
 
 Generate a confession and talking points.`,
-          },
-        ],
-      }),
+        },
+      ],
     });
 
     const data = await response.json();
@@ -821,9 +766,9 @@ class OllamaClient implements LLMClient {
     const prompt = buildReportPrompt('general', audience, dataSummaries);
 
     const response = await generateReport({
-      templateType: 'general',
+      template_type: 'general',
       audience: audience as 'self' | 'team' | 'exec',
-      dataSummary: dataSummaries,
+      data_summary: dataSummaries,
     });
 
     return response.markdown;
@@ -851,8 +796,8 @@ class OllamaClient implements LLMClient {
       {
         role,
         question,
-        contextSummary: dataSummaries,
-        projectMeta,
+        context_summary: dataSummaries,
+        project_meta: projectMeta,
       },
       (chunk) => {
         fullAnswer += chunk;
@@ -890,8 +835,8 @@ class OllamaClient implements LLMClient {
       {
         role: 'analysis',
         question: 'Summarize this table',
-        contextSummary: prompt,
-        projectMeta: { name: 'Table Summary', audience: 'self' },
+        context_summary: prompt,
+        project_meta: { name: 'Table Summary', audience: 'self' },
       },
       (chunk) => {
         fullAnswer += chunk;
@@ -913,8 +858,8 @@ class OllamaClient implements LLMClient {
       {
         role: 'analysis',
         question: 'Describe this chart',
-        contextSummary: prompt,
-        projectMeta: { name: 'Chart Description', audience: 'self' },
+        context_summary: prompt,
+        project_meta: { name: 'Chart Description', audience: 'self' },
       },
       (chunk) => {
         fullAnswer += chunk;
@@ -946,8 +891,8 @@ class OllamaClient implements LLMClient {
       {
         role,
         question,
-        contextSummary: dataSummaries,
-        projectMeta: {
+        context_summary: dataSummaries,
+        project_meta: {
           name: 'Quick Confession',
           audience: audience as 'self' | 'team' | 'exec',
         },
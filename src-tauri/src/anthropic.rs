@@ -0,0 +1,498 @@
+// Anthropic engine provider
+// Calls the Messages API with the stored API key; base_url can point at a local mock server for testing
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+use crate::api_key::{self, KeySources};
+use crate::engine_config::GenerationSettings;
+use crate::provider::{secret_header, ChunkHandler, CompletionRequest, EngineProvider};
+
+pub const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(10);
+const CHAT_TIMEOUT: Duration = Duration::from_secs(90);
+const COMPLETION_TIMEOUT: Duration = Duration::from_secs(120);
+// The Messages API requires max_tokens; used when the pack leaves it unset
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+pub struct AnthropicProvider {
+    base_url: String,
+    credential_id: String,
+    key_sources: KeySources,
+}
+
+impl AnthropicProvider {
+    pub fn new(base_url: &str, credential_id: &str, key_sources: &KeySources) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            credential_id: credential_id.to_string(),
+            key_sources: key_sources.clone(),
+        }
+    }
+
+    fn client(&self, timeout: Duration) -> Result<reqwest::Client, String> {
+        reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))
+    }
+
+    // `record_use` for requests that spend the key; health checks and listing don't count
+    fn api_key(&self, record_use: bool) -> Result<Zeroizing<String>, String> {
+        let key = if record_use {
+            api_key::use_api_key(&self.credential_id, &self.key_sources)
+        } else {
+            api_key::get_api_key(&self.credential_id, &self.key_sources)
+        };
+        key?.ok_or_else(|| {
+            "No Anthropic API key stored. Please configure your API key in settings.".to_string()
+        })
+    }
+
+    async fn get_models(&self, timeout: Duration) -> Result<serde_json::Value, String> {
+        let key = self.api_key(false)?;
+        let response = self
+            .client(timeout)?
+            .get(format!("{}/v1/models?limit=1000", self.base_url))
+            .header("x-api-key", secret_header(&key))
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .send()
+            .await
+            .map_err(|e| format!("Could not reach Anthropic at {}: {}", self.base_url, e))?;
+        drop(key);
+
+        read_json(response).await
+    }
+
+    // A Messages API call with a body built by the caller, for the frontend proxy
+    pub async fn messages(&self, body: &serde_json::Value) -> Result<serde_json::Value, String> {
+        let response = self.post_json(body, COMPLETION_TIMEOUT).await?;
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Anthropic response: {}", e))
+    }
+
+    async fn post_messages(
+        &self,
+        request: &CompletionRequest,
+        stream: bool,
+        timeout: Duration,
+    ) -> Result<reqwest::Response, String> {
+        self.post_json(&messages_body(request, stream)?, timeout).await
+    }
+
+    async fn post_json(
+        &self,
+        body: &serde_json::Value,
+        timeout: Duration,
+    ) -> Result<reqwest::Response, String> {
+        let key = self.api_key(true)?;
+        let response = self
+            .client(timeout)?
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("x-api-key", secret_header(&key))
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| format!("Could not reach Anthropic at {}: {}", self.base_url, e))?;
+        drop(key);
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.json().await.unwrap_or(serde_json::Value::Null);
+            return Err(api_error_message(status.as_u16(), &body));
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl EngineProvider for AnthropicProvider {
+    async fn health(&self) -> Result<(), String> {
+        self.get_models(HEALTH_TIMEOUT).await.map(|_| ())
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, String> {
+        let json = self.get_models(HEALTH_TIMEOUT).await?;
+        let models = json
+            .get("data")
+            .and_then(|d| d.as_array())
+            .ok_or("Invalid response format from Anthropic")?;
+
+        Ok(models
+            .iter()
+            .filter_map(|m| m.get("id").and_then(|id| id.as_str()).map(|s| s.to_string()))
+            .collect())
+    }
+
+    // The shipped defaults are tuned for local models, and Claude rejects some combinations of
+    // them (temperature with top_p), so only what the pack sets is sent
+    fn generation_settings(
+        &self,
+        _role: &str,
+        pack_settings: &GenerationSettings,
+    ) -> GenerationSettings {
+        pack_settings.clone()
+    }
+
+    // Listing models costs no tokens
+    async fn check_key(&self, key: &str) -> Result<Option<u16>, String> {
+        let response = self
+            .client(HEALTH_TIMEOUT)?
+            .get(format!("{}/v1/models", self.base_url))
+            .header("x-api-key", secret_header(key))
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .send()
+            .await
+            .map_err(|e| format!("Could not reach Anthropic at {}: {}", self.base_url, e))?;
+
+        Ok(Some(response.status().as_u16()))
+    }
+
+    // Models are hosted, so installing only checks the key can use it
+    async fn install_model(&self, model: &str) -> Result<(), String> {
+        if self.list_models().await?.iter().any(|m| m == model) {
+            Ok(())
+        } else {
+            Err(format!("Model {} is not available to this Anthropic API key", model))
+        }
+    }
+
+    async fn chat_stream(
+        &self,
+        request: CompletionRequest,
+        on_chunk: &mut ChunkHandler<'_>,
+    ) -> Result<String, String> {
+        let response = self.post_messages(&request, true, CHAT_TIMEOUT).await?;
+
+        // Server-sent events: `event:` and `data:` lines, events separated by a blank line.
+        // Every data payload carries its own "type", so the event lines can be ignored.
+        let mut full_content = String::new();
+        let mut pending: Vec<u8> = Vec::new();
+        let mut stream = response.bytes_stream();
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| format!("Stream error: {}", e))?;
+            pending.extend_from_slice(&chunk);
+
+            while let Some(newline) = pending.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=newline).collect();
+                let line = String::from_utf8_lossy(&line);
+                let data = match line.trim().strip_prefix("data:") {
+                    Some(data) => data.trim(),
+                    None => continue,
+                };
+
+                let json: serde_json::Value = match serde_json::from_str(data) {
+                    Ok(json) => json,
+                    Err(_) => continue,
+                };
+
+                match json.get("type").and_then(|t| t.as_str()) {
+                    Some("content_block_delta") => {
+                        let text = json
+                            .get("delta")
+                            .filter(|d| d.get("type").and_then(|t| t.as_str()) == Some("text_delta"))
+                            .and_then(|d| d.get("text"))
+                            .and_then(|t| t.as_str())
+                            .unwrap_or_default();
+                        if text.is_empty() {
+                            continue;
+                        }
+                        full_content.push_str(text);
+                        on_chunk(text)?;
+                    }
+                    // Errors after the response started arrive as an event, not a status code
+                    Some("error") => return Err(api_error_message(200, &json)),
+                    Some("message_stop") => return Ok(full_content),
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(full_content)
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<String, String> {
+        let response = self.post_messages(&request, false, COMPLETION_TIMEOUT).await?;
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        let blocks = json
+            .get("content")
+            .and_then(|c| c.as_array())
+            .ok_or("Invalid response format from Anthropic")?;
+
+        Ok(blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect())
+    }
+}
+
+// System messages go in the top-level `system` field; the rest stay in order
+// Settings Anthropic would reject are an error here, with a message naming the pack setting
+fn messages_body(request: &CompletionRequest, stream: bool) -> Result<serde_json::Value, String> {
+    let system: Vec<&str> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect();
+    let messages: Vec<serde_json::Value> = request
+        .messages
+        .iter()
+        .filter(|m| m.role != "system")
+        .map(|m| serde_json::json!({ "role": m.role, "content": m.content }))
+        .collect();
+
+    let settings = &request.settings;
+    let mut body = serde_json::json!({
+        "model": request.model,
+        "messages": messages,
+        "max_tokens": settings.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        "stream": stream,
+    });
+
+    if !system.is_empty() {
+        body["system"] = system.join("\n\n").into();
+    }
+    // num_ctx and seed have no equivalent
+    if settings.temperature.is_some() && settings.top_p.is_some() {
+        return Err(
+            "Anthropic accepts temperature or top_p, not both. Remove one from the pack's settings."
+                .to_string(),
+        );
+    }
+    if let Some(temperature) = settings.temperature {
+        if temperature > 1.0 {
+            return Err(format!(
+                "Anthropic accepts a temperature between 0 and 1, but the pack sets {}",
+                temperature
+            ));
+        }
+        body["temperature"] = temperature.into();
+    }
+    if let Some(top_p) = settings.top_p {
+        body["top_p"] = top_p.into();
+    }
+    if let Some(top_k) = settings.top_k {
+        body["top_k"] = top_k.into();
+    }
+    if !settings.stop.is_empty() {
+        body["stop_sequences"] = settings.stop.clone().into();
+    }
+
+    Ok(body)
+}
+
+async fn read_json(response: reqwest::Response) -> Result<serde_json::Value, String> {
+    let status = response.status();
+    let json: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
+
+    if status.is_success() {
+        Ok(json)
+    } else {
+        Err(api_error_message(status.as_u16(), &json))
+    }
+}
+
+// Turn an Anthropic error body ({"type": "error", "error": {"type", "message"}}) into a
+// message the user can act on
+pub fn api_error_message(status: u16, body: &serde_json::Value) -> String {
+    let error = body.get("error");
+    let kind = error.and_then(|e| e.get("type")).and_then(|t| t.as_str()).unwrap_or("");
+    let detail = error
+        .and_then(|e| e.get("message"))
+        .and_then(|m| m.as_str())
+        .unwrap_or("unknown error");
+
+    match (kind, status) {
+        ("authentication_error", _) | (_, 401) => {
+            "Invalid Anthropic API key. Please check your key in settings.".to_string()
+        }
+        ("permission_error", _) | (_, 403) => {
+            format!("This Anthropic API key is not allowed to do that: {}", detail)
+        }
+        ("not_found_error", _) | (_, 404) => format!("Anthropic could not find that: {}", detail),
+        ("request_too_large", _) | (_, 413) => {
+            "The request is too large for Anthropic. Try a shorter data summary.".to_string()
+        }
+        ("rate_limit_error", _) | (_, 429) => {
+            "Anthropic rate limit reached. Please wait a moment and try again.".to_string()
+        }
+        ("overloaded_error", _) | (_, 529) => {
+            "Anthropic is temporarily overloaded. Please try again shortly.".to_string()
+        }
+        ("invalid_request_error", _) | (_, 400) => {
+            format!("Anthropic rejected the request: {}", detail)
+        }
+        _ => format!("Anthropic error ({}): {}", status, detail),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{self, Response};
+    use crate::provider::ChatMessage;
+
+    // A key provisioned through a file, so tests never touch the OS secret store
+    fn test_provider(base_url: &str, name: &str) -> AnthropicProvider {
+        let dir = std::env::temp_dir().join(format!("dataconfessional-anthropic-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key_file = dir.join(format!("{}.key", name));
+        std::fs::write(&key_file, "sk-ant-test").unwrap();
+
+        let sources = KeySources {
+            credential_id: "anthropic-test".to_string(),
+            api_key_env: None,
+            api_key_file: Some(key_file.display().to_string()),
+            // Usage is recorded in the credential index; keep it out of the real app data
+            index_dir: Some(dir),
+        };
+        AnthropicProvider::new(base_url, "anthropic-test", &sources)
+    }
+
+    fn request(settings: GenerationSettings) -> CompletionRequest {
+        CompletionRequest {
+            model: "claude-test".to_string(),
+            messages: vec![ChatMessage::system("Be brief."), ChatMessage::user("Hi")],
+            settings,
+        }
+    }
+
+    #[test]
+    fn only_settings_the_pack_sets_are_sent() {
+        let provider = AnthropicProvider::new(ANTHROPIC_BASE_URL, "default", &KeySources::default());
+        let settings = provider.generation_settings("analysis", &GenerationSettings::default());
+        let body = messages_body(&request(settings), false).unwrap();
+
+        assert_eq!(body["system"], "Be brief.");
+        assert_eq!(body["max_tokens"], DEFAULT_MAX_TOKENS);
+        for field in ["temperature", "top_p", "top_k", "stop_sequences"] {
+            assert!(body.get(field).is_none(), "{} should not be sent", field);
+        }
+    }
+
+    #[test]
+    fn settings_anthropic_rejects_are_errors() {
+        let both = GenerationSettings {
+            temperature: Some(0.5),
+            top_p: Some(0.9),
+            ..GenerationSettings::default()
+        };
+        assert!(messages_body(&request(both), false).is_err());
+
+        let too_hot = GenerationSettings {
+            temperature: Some(1.5),
+            ..GenerationSettings::default()
+        };
+        assert!(messages_body(&request(too_hot), false).is_err());
+    }
+
+    #[tokio::test]
+    async fn streamed_text_deltas_are_passed_on_in_order() {
+        let events = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hello\"}}\n\n",
+            "event: ping\n",
+            "data: {\"type\":\"ping\"}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\", world\"}}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+        let (base_url, server) = mock_server::serve(vec![Response::sse(events)]);
+        let provider = test_provider(&base_url, "stream");
+
+        let mut chunks = Vec::new();
+        let content = provider
+            .chat_stream(request(GenerationSettings::default()), &mut |chunk| {
+                chunks.push(chunk.to_string());
+                Ok(())
+            })
+            .await
+            .unwrap();
+        let requests = server.join().unwrap();
+
+        assert_eq!(content, "Hello, world");
+        assert_eq!(chunks, vec!["Hello", ", world"]);
+        assert!(requests[0].starts_with("POST /v1/messages "));
+        assert!(requests[0].contains("x-api-key: sk-ant-test"));
+        assert!(requests[0].contains("\"stream\":true"));
+    }
+
+    #[tokio::test]
+    async fn error_event_mid_stream_is_reported() {
+        let events = concat!(
+            "event: error\n",
+            "data: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n",
+        );
+        let (base_url, server) = mock_server::serve(vec![Response::sse(events)]);
+        let provider = test_provider(&base_url, "stream-error");
+
+        let result = provider
+            .chat_stream(request(GenerationSettings::default()), &mut |_| Ok(()))
+            .await;
+        server.join().unwrap();
+
+        assert!(result.unwrap_err().contains("overloaded"));
+    }
+
+    #[tokio::test]
+    async fn error_statuses_become_actionable_messages() {
+        let cases = [
+            (401, "authentication_error", "Invalid Anthropic API key"),
+            (429, "rate_limit_error", "rate limit"),
+            (400, "invalid_request_error", "rejected the request: bad input"),
+            (529, "overloaded_error", "overloaded"),
+        ];
+        for (status, kind, expected) in cases {
+            let body = format!(
+                r#"{{"type":"error","error":{{"type":"{}","message":"bad input"}}}}"#,
+                kind
+            );
+            let (base_url, server) = mock_server::serve(vec![Response::json(status, &body)]);
+            let provider = test_provider(&base_url, kind);
+
+            let result = provider.complete(request(GenerationSettings::default())).await;
+            server.join().unwrap();
+
+            let error = result.unwrap_err();
+            assert!(error.contains(expected), "HTTP {}: {}", status, error);
+        }
+    }
+
+    #[tokio::test]
+    async fn key_lookup_errors_are_not_reported_as_a_missing_key() {
+        let sources = KeySources {
+            credential_id: "anthropic-test".to_string(),
+            api_key_env: None,
+            api_key_file: Some("/nonexistent/dataconfessional/anthropic.key".to_string()),
+            index_dir: None,
+        };
+        // Nothing listens here; the lookup fails before any request is made
+        let provider = AnthropicProvider::new(&mock_server::closed_url(), "anthropic-test", &sources);
+
+        let error = provider.list_models().await.unwrap_err();
+        assert!(error.contains("Failed to read API key file"), "{}", error);
+        assert!(!error.contains("No Anthropic API key stored"), "{}", error);
+    }
+}
@@ -1,93 +1,1128 @@
-// API Key storage using Windows Credential Manager
-// Falls back to encrypted file if Credential Manager is unavailable
+// API Key storage behind a pluggable SecretStore
+// Uses Windows Credential Manager or the Linux Secret Service, falling back to an encrypted file
 
-use std::ptr;
-use winapi::um::wincred::*;
-use winapi::um::winbase::*;
-use winapi::um::winnls::*;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::app_paths;
+use crate::engine_config::EngineConfig;
+
+pub const DEFAULT_CREDENTIAL_ID: &str = "default";
 
 const CREDENTIAL_TARGET: &str = "DataConfessional_APIKey";
+const VAULT_FILE_NAME: &str = "keys.vault";
+const INSTALL_SECRET_FILE_NAME: &str = "keys.vault.secret";
+const INDEX_FILE_NAME: &str = "credentials.json";
 
-pub fn store_api_key(key: &str) -> Result<(), String> {
-    unsafe {
-        // Convert string to wide string (UTF-16)
-        let target_wide: Vec<u16> = CREDENTIAL_TARGET.encode_utf16().chain(Some(0)).collect();
-        let key_bytes = key.as_bytes();
-        let key_wide: Vec<u16> = key.encode_utf16().collect();
-        let key_blob_size = (key_wide.len() * 2) as u32;
-
-        let mut credential = CREDENTIALW {
-            Flags: 0,
-            Type: CRED_TYPE_GENERIC,
-            TargetName: target_wide.as_ptr() as *mut u16,
-            Comment: ptr::null_mut(),
-            LastWritten: FILETIME {
-                dwLowDateTime: 0,
-                dwHighDateTime: 0,
-            },
-            CredentialBlobSize: key_blob_size,
-            CredentialBlob: key_wide.as_ptr() as *mut u8,
-            Persist: CRED_PERSIST_LOCAL_MACHINE,
-            AttributeCount: 0,
-            Attributes: ptr::null_mut(),
-            TargetAlias: ptr::null_mut(),
-            UserName: ptr::null_mut(),
-        };
+const DAY_SECS: u64 = 24 * 60 * 60;
+const EXPIRY_WARNING_SECS: u64 = 14 * DAY_SECS;
+const ROTATION_INTERVAL_SECS: u64 = 90 * DAY_SECS;
+// last_used_at is only rewritten once it is this old, so busy chats don't rewrite the index
+const LAST_USED_RESOLUTION_SECS: u64 = 60;
+
+// A backend that can hold secrets keyed by a target name
+// Secrets come back as Zeroizing so they are wiped from memory once dropped. `get` returns
+// Ok(None) only when nothing is stored under the target; a store it cannot read is an error.
+pub trait SecretStore {
+    fn is_available(&self) -> bool;
+    fn store(&self, target: &str, secret: &str) -> Result<(), String>;
+    fn get(&self, target: &str) -> Result<Option<Zeroizing<String>>, String>;
+    fn delete(&self, target: &str) -> Result<(), String>;
+}
+
+// Windows Credential Manager backend
+
+#[cfg(windows)]
+pub struct CredentialManagerStore;
+
+#[cfg(windows)]
+impl SecretStore for CredentialManagerStore {
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn store(&self, target: &str, secret: &str) -> Result<(), String> {
+        use std::ptr;
+        use winapi::shared::minwindef::FILETIME;
+        use winapi::um::errhandlingapi::GetLastError;
+        use winapi::um::wincred::*;
+
+        unsafe {
+            // Convert string to wide string (UTF-16)
+            let target_wide: Vec<u16> = target.encode_utf16().chain(Some(0)).collect();
+            let secret_wide: Vec<u16> = secret.encode_utf16().collect();
+            let blob_size = (secret_wide.len() * 2) as u32;
+
+            let mut credential = CREDENTIALW {
+                Flags: 0,
+                Type: CRED_TYPE_GENERIC,
+                TargetName: target_wide.as_ptr() as *mut u16,
+                Comment: ptr::null_mut(),
+                LastWritten: FILETIME {
+                    dwLowDateTime: 0,
+                    dwHighDateTime: 0,
+                },
+                CredentialBlobSize: blob_size,
+                CredentialBlob: secret_wide.as_ptr() as *mut u8,
+                Persist: CRED_PERSIST_LOCAL_MACHINE,
+                AttributeCount: 0,
+                Attributes: ptr::null_mut(),
+                TargetAlias: ptr::null_mut(),
+                UserName: ptr::null_mut(),
+            };
+
+            let result = CredWriteW(&mut credential, 0);
+            if result != 0 {
+                Ok(())
+            } else {
+                let error = GetLastError();
+                Err(format!("Failed to store API key in Credential Manager. Error code: {}", error))
+            }
+        }
+    }
+
+    fn get(&self, target: &str) -> Result<Option<Zeroizing<String>>, String> {
+        use std::ptr;
+        use winapi::shared::winerror::ERROR_NOT_FOUND;
+        use winapi::um::errhandlingapi::GetLastError;
+        use winapi::um::wincred::*;
+
+        unsafe {
+            let target_wide: Vec<u16> = target.encode_utf16().chain(Some(0)).collect();
+            let mut credential: PCREDENTIALW = ptr::null_mut();
 
-        let result = CredWriteW(&credential as *const _, 0);
-        if result != 0 {
+            let result = CredReadW(target_wide.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential);
+
+            if result != 0 && !credential.is_null() {
+                let blob_size = (*credential).CredentialBlobSize as usize;
+                let blob_ptr = (*credential).CredentialBlob;
+
+                // Convert UTF-16 back to String
+                let blob_slice = std::slice::from_raw_parts(blob_ptr as *const u16, blob_size / 2);
+                let decoded = String::from_utf16(blob_slice).map(Zeroizing::new);
+
+                // Wipe the blob before handing it back to the OS
+                std::slice::from_raw_parts_mut(blob_ptr, blob_size).zeroize();
+                CredFree(credential as *mut _);
+                decoded
+                    .map(Some)
+                    .map_err(|e| format!("Failed to decode API key: {}", e))
+            } else {
+                match GetLastError() {
+                    ERROR_NOT_FOUND => Ok(None),
+                    error => Err(format!(
+                        "Failed to read API key from Credential Manager. Error code: {}",
+                        error
+                    )),
+                }
+            }
+        }
+    }
+
+    fn delete(&self, target: &str) -> Result<(), String> {
+        use winapi::um::errhandlingapi::GetLastError;
+        use winapi::um::wincred::*;
+
+        unsafe {
+            let target_wide: Vec<u16> = target.encode_utf16().chain(Some(0)).collect();
+            let result = CredDeleteW(target_wide.as_ptr(), CRED_TYPE_GENERIC, 0);
+
+            if result != 0 {
+                Ok(())
+            } else {
+                let error = GetLastError();
+                Err(format!("Failed to delete API key. Error code: {}", error))
+            }
+        }
+    }
+}
+
+// Linux Secret Service backend (GNOME Keyring, KWallet) via the `secret-tool` CLI
+
+#[cfg(target_os = "linux")]
+pub struct SecretServiceStore;
+
+#[cfg(target_os = "linux")]
+impl SecretServiceStore {
+    const SERVICE: &'static str = "DataConfessional";
+
+    fn run(args: &[&str], stdin: Option<&str>) -> Result<std::process::Output, String> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let mut child = Command::new("secret-tool")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run secret-tool: {}", e))?;
+
+        if let Some(input) = stdin {
+            child
+                .stdin
+                .take()
+                .ok_or("Failed to open secret-tool stdin")?
+                .write_all(input.as_bytes())
+                .map_err(|e| format!("Failed to write to secret-tool: {}", e))?;
+        } else {
+            drop(child.stdin.take());
+        }
+
+        child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to wait for secret-tool: {}", e))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl SecretStore for SecretServiceStore {
+    fn is_available(&self) -> bool {
+        use std::sync::OnceLock;
+
+        // secret-tool talks to the keyring over the session bus. Checked once, without
+        // touching the keyring, so probing neither reads secrets nor prompts to unlock it
+        static AVAILABLE: OnceLock<bool> = OnceLock::new();
+        *AVAILABLE.get_or_init(|| {
+            std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+                && std::env::var_os("PATH")
+                    .map(|path| std::env::split_paths(&path).any(|dir| dir.join("secret-tool").is_file()))
+                    .unwrap_or(false)
+        })
+    }
+
+    fn store(&self, target: &str, secret: &str) -> Result<(), String> {
+        let label = format!("--label=DataConfessional ({})", target);
+        let output = Self::run(
+            &["store", &label, "service", Self::SERVICE, "credential", target],
+            Some(secret),
+        )?;
+
+        if output.status.success() {
             Ok(())
         } else {
-            let error = GetLastError();
-            Err(format!("Failed to store API key in Credential Manager. Error code: {}", error))
+            Err(format!(
+                "Failed to store API key in Secret Service: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
+    fn get(&self, target: &str) -> Result<Option<Zeroizing<String>>, String> {
+        let output = Self::run(&["lookup", "service", Self::SERVICE, "credential", target], None)?;
+
+        // A lookup that finds nothing exits non-zero without saying anything
+        if output.status.success() && !output.stdout.is_empty() {
+            String::from_utf8(output.stdout)
+                .map(|secret| Some(Zeroizing::new(secret)))
+                .map_err(|e| format!("Failed to decode API key: {}", e))
+        } else if output.stderr.iter().all(u8::is_ascii_whitespace) {
+            Ok(None)
+        } else {
+            Err(format!(
+                "Failed to read API key from Secret Service: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
+    fn delete(&self, target: &str) -> Result<(), String> {
+        let output = Self::run(&["clear", "service", Self::SERVICE, "credential", target], None)?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Failed to delete API key: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
         }
     }
 }
 
-pub fn get_api_key() -> Result<String, String> {
+// Encrypted file backend, used when the OS credential store is missing or failing
+// The vault key is derived with Argon2id from a user passphrase if one was given. Otherwise
+// Windows seals a random secret with DPAPI to the user's login, and other platforms keep a
+// random per-install secret in a 0600 file beside the vault. That file only stops other
+// local users; anyone who can read this account's files can open the vault, so set a
+// passphrase for more than that
+
+static VAULT_PASSPHRASE: Mutex<Option<Zeroizing<String>>> = Mutex::new(None);
+
+const VAULT_VERSION: u32 = 1;
+const KEY_SOURCE_PASSPHRASE: &str = "passphrase";
+const KEY_SOURCE_INSTALL: &str = "install";
+const KEY_SOURCE_DPAPI: &str = "dpapi";
+
+type VaultEntries = HashMap<String, Zeroizing<String>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    key_source: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+    // The DPAPI-sealed vault secret, for the "dpapi" key source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    protected_key: Option<String>,
+}
+
+pub struct EncryptedFileStore {
+    path: PathBuf,
+}
+
+impl EncryptedFileStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn install_secret_path(&self) -> PathBuf {
+        self.path.with_file_name(INSTALL_SECRET_FILE_NAME)
+    }
+
+    // The per-install secret; created on first use when `create` is set
+    fn install_secret(&self, create: bool) -> Result<Zeroizing<Vec<u8>>, String> {
+        let path = self.install_secret_path();
+        if path.exists() {
+            let secret = fs::read(&path)
+                .map(Zeroizing::new)
+                .map_err(|e| format!("Failed to read key vault secret: {}", e))?;
+            if secret.len() != 32 {
+                return Err("Key vault secret is damaged".to_string());
+            }
+            return Ok(secret);
+        }
+        if !create {
+            return Err(format!(
+                "Key vault secret is missing; the vault cannot be opened without {}",
+                path.display()
+            ));
+        }
+
+        let mut secret = Zeroizing::new(vec![0u8; 32]);
+        OsRng.fill_bytes(&mut secret);
+        app_paths::write_atomic(&path, &secret, true)
+            .map_err(|e| format!("Failed to write key vault secret: {}", e))?;
+        Ok(secret)
+    }
+
+    fn passphrase() -> Option<Zeroizing<String>> {
+        std::env::var("DATACONFESSIONAL_VAULT_PASSPHRASE")
+            .ok()
+            .filter(|p| !p.is_empty())
+            .map(Zeroizing::new)
+            .or_else(|| VAULT_PASSPHRASE.lock().ok().and_then(|p| p.clone()))
+    }
+
+    // The input to key derivation; `protected_key` is only read for the DPAPI source
+    fn secret(&self, key_source: &str, protected_key: Option<&str>) -> Result<Zeroizing<Vec<u8>>, String> {
+        match key_source {
+            KEY_SOURCE_PASSPHRASE => Self::passphrase()
+                .map(|p| Zeroizing::new(p.as_bytes().to_vec()))
+                .ok_or("Key vault is locked. Enter the vault passphrase to unlock it.".to_string()),
+            KEY_SOURCE_INSTALL => self.install_secret(false),
+            KEY_SOURCE_DPAPI => {
+                let sealed = protected_key.ok_or("Key vault is missing its protected key")?;
+                let sealed = BASE64
+                    .decode(sealed)
+                    .map_err(|e| format!("Failed to decode key vault: {}", e))?;
+                dpapi_unprotect(&sealed)
+            }
+            other => Err(format!("Unknown key vault key source: {}", other)),
+        }
+    }
+
+    fn cipher(secret: &[u8], salt: &[u8]) -> Result<Aes256Gcm, String> {
+        let mut key = Key::<Aes256Gcm>::default();
+        let derived = Argon2::default().hash_password_into(secret, salt, &mut key);
+        let cipher = derived.map(|_| Aes256Gcm::new(&key));
+        key.as_mut_slice().zeroize();
+
+        cipher.map_err(|e| format!("Failed to derive key vault key: {}", e))
+    }
+
+    fn read_vault(&self) -> Result<Option<VaultFile>, String> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read key vault: {}", e))?;
+        let vault: VaultFile = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse key vault: {}", e))?;
+
+        if vault.version > VAULT_VERSION {
+            return Err(format!(
+                "Key vault was written by a newer version of the app (version {})",
+                vault.version
+            ));
+        }
+
+        Ok(Some(vault))
+    }
+
+    fn read_entries(&self) -> Result<VaultEntries, String> {
+        let vault = match self.read_vault()? {
+            Some(vault) => vault,
+            None => return Ok(VaultEntries::new()),
+        };
+
+        let decode = |field: &str| {
+            BASE64
+                .decode(field)
+                .map_err(|e| format!("Failed to decode key vault: {}", e))
+        };
+        let salt = decode(&vault.salt)?;
+        let nonce = decode(&vault.nonce)?;
+        let ciphertext = decode(&vault.ciphertext)?;
+
+        let secret = self.secret(&vault.key_source, vault.protected_key.as_deref())?;
+        let cipher = Self::cipher(&secret, &salt)?;
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map(Zeroizing::new)
+            .map_err(|_| match vault.key_source.as_str() {
+                KEY_SOURCE_PASSPHRASE => "Failed to decrypt key vault: wrong passphrase".to_string(),
+                _ => "Failed to decrypt key vault (was its secret file replaced?)".to_string(),
+            })?;
+
+        serde_json::from_slice(&plaintext).map_err(|e| format!("Failed to parse key vault: {}", e))
+    }
+
+    fn write_entries(&self, entries: &VaultEntries) -> Result<(), String> {
+        let plaintext = serde_json::to_vec(entries)
+            .map(Zeroizing::new)
+            .map_err(|e| format!("Failed to serialize key vault: {}", e))?;
+
+        // Keep a passphrase-protected vault protected, even if the passphrase is now unknown
+        let existing_source = self.read_vault().ok().flatten().map(|v| v.key_source);
+        let key_source = match (existing_source.as_deref(), Self::passphrase()) {
+            (_, Some(_)) | (Some(KEY_SOURCE_PASSPHRASE), None) => KEY_SOURCE_PASSPHRASE,
+            _ if cfg!(windows) => KEY_SOURCE_DPAPI,
+            _ => KEY_SOURCE_INSTALL,
+        };
+
+        // A fresh DPAPI secret per write; only this Windows account can unseal it
+        let (secret, protected_key) = if key_source == KEY_SOURCE_DPAPI {
+            let mut secret = Zeroizing::new(vec![0u8; 32]);
+            OsRng.fill_bytes(&mut secret);
+            let sealed = dpapi_protect(&secret)?;
+            (secret, Some(BASE64.encode(sealed)))
+        } else if key_source == KEY_SOURCE_INSTALL {
+            (self.install_secret(true)?, None)
+        } else {
+            (self.secret(key_source, None)?, None)
+        };
+
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+
+        let cipher = Self::cipher(&secret, &salt)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_ref())
+            .map_err(|_| "Failed to encrypt key vault".to_string())?;
+
+        let vault = VaultFile {
+            version: VAULT_VERSION,
+            key_source: key_source.to_string(),
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+            protected_key,
+        };
+        let content = serde_json::to_string_pretty(&vault)
+            .map_err(|e| format!("Failed to serialize key vault: {}", e))?;
+
+        app_paths::write_atomic(&self.path, content.as_bytes(), true)
+            .map_err(|e| format!("Failed to write key vault: {}", e))
+    }
+}
+
+#[cfg(windows)]
+fn dpapi_protect(data: &[u8]) -> Result<Vec<u8>, String> {
+    use std::ptr;
+    use winapi::um::dpapi::{CryptProtectData, CRYPTPROTECT_UI_FORBIDDEN};
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::winbase::LocalFree;
+    use winapi::um::wincrypt::DATA_BLOB;
+
     unsafe {
-        let target_wide: Vec<u16> = CREDENTIAL_TARGET.encode_utf16().chain(Some(0)).collect();
-        let mut credential: *mut CREDENTIALW = ptr::null_mut();
-
-        let result = CredReadW(
-            target_wide.as_ptr() as *const _,
-            CRED_TYPE_GENERIC,
-            0,
-            &mut credential,
+        let mut input = DATA_BLOB {
+            cbData: data.len() as u32,
+            pbData: data.as_ptr() as *mut u8,
+        };
+        let mut output: DATA_BLOB = std::mem::zeroed();
+        let ok = CryptProtectData(
+            &mut input,
+            ptr::null(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
         );
+        if ok == 0 {
+            return Err(format!("Failed to protect key vault secret. Error code: {}", GetLastError()));
+        }
 
-        if result != 0 && !credential.is_null() {
-            let blob_size = (*credential).CredentialBlobSize as usize;
-            let blob_ptr = (*credential).CredentialBlob;
+        let sealed = std::slice::from_raw_parts(output.pbData, output.cbData as usize).to_vec();
+        LocalFree(output.pbData as *mut _);
+        Ok(sealed)
+    }
+}
 
-            // Convert UTF-16 back to String
-            let blob_slice = std::slice::from_raw_parts(blob_ptr as *const u16, blob_size / 2);
-            let key = String::from_utf16(blob_slice)
-                .map_err(|e| format!("Failed to decode API key: {}", e))?;
+#[cfg(windows)]
+fn dpapi_unprotect(sealed: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+    use std::ptr;
+    use winapi::um::dpapi::{CryptUnprotectData, CRYPTPROTECT_UI_FORBIDDEN};
+    use winapi::um::winbase::LocalFree;
+    use winapi::um::wincrypt::DATA_BLOB;
 
-            CredFree(credential as *mut _);
-            Ok(key)
-        } else {
-            Err("API key not found in Credential Manager".to_string())
+    unsafe {
+        let mut input = DATA_BLOB {
+            cbData: sealed.len() as u32,
+            pbData: sealed.as_ptr() as *mut u8,
+        };
+        let mut output: DATA_BLOB = std::mem::zeroed();
+        let ok = CryptUnprotectData(
+            &mut input,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        );
+        if ok == 0 {
+            return Err("Failed to decrypt key vault (was it created by another Windows user?)".to_string());
         }
+
+        let plain = std::slice::from_raw_parts_mut(output.pbData, output.cbData as usize);
+        let secret = Zeroizing::new(plain.to_vec());
+        plain.zeroize();
+        LocalFree(output.pbData as *mut _);
+        Ok(secret)
     }
 }
 
-pub fn has_api_key() -> bool {
-    get_api_key().is_ok()
+#[cfg(not(windows))]
+fn dpapi_protect(_data: &[u8]) -> Result<Vec<u8>, String> {
+    Err("DPAPI is only available on Windows".to_string())
 }
 
-pub fn delete_api_key() -> Result<(), String> {
-    unsafe {
-        let target_wide: Vec<u16> = CREDENTIAL_TARGET.encode_utf16().chain(Some(0)).collect();
-        let result = CredDeleteW(target_wide.as_ptr() as *const _, CRED_TYPE_GENERIC, 0);
+#[cfg(not(windows))]
+fn dpapi_unprotect(_sealed: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+    Err("Key vault was sealed with Windows DPAPI and can only be opened by that Windows user".to_string())
+}
 
-        if result != 0 {
-            Ok(())
-        } else {
-            let error = GetLastError();
-            Err(format!("Failed to delete API key. Error code: {}", error))
+impl SecretStore for EncryptedFileStore {
+    fn is_available(&self) -> bool {
+        self.path.parent().map(|dir| dir.exists()).unwrap_or(false)
+    }
+
+    fn store(&self, target: &str, secret: &str) -> Result<(), String> {
+        let mut entries = self.read_entries()?;
+        entries.insert(target.to_string(), Zeroizing::new(secret.to_string()));
+        self.write_entries(&entries)
+    }
+
+    fn get(&self, target: &str) -> Result<Option<Zeroizing<String>>, String> {
+        Ok(self.read_entries()?.remove(target))
+    }
+
+    fn delete(&self, target: &str) -> Result<(), String> {
+        let mut entries = self.read_entries()?;
+        if entries.remove(target).is_none() {
+            return Err("API key not found in key vault".to_string());
+        }
+        self.write_entries(&entries)
+    }
+}
+
+// OS credential store with the encrypted vault behind it
+// Writes that the OS store rejects land in the vault; reads check both
+
+pub struct FallbackStore {
+    primary: Option<Box<dyn SecretStore>>,
+    vault: EncryptedFileStore,
+}
+
+impl SecretStore for FallbackStore {
+    fn is_available(&self) -> bool {
+        self.primary.as_ref().map(|p| p.is_available()).unwrap_or(false) || self.vault.is_available()
+    }
+
+    fn store(&self, target: &str, secret: &str) -> Result<(), String> {
+        if let Some(primary) = &self.primary {
+            match primary.store(target, secret) {
+                Ok(()) => {
+                    // Don't leave a stale copy behind from an earlier fallback
+                    let _ = self.vault.delete(target);
+                    return Ok(());
+                }
+                Err(primary_err) => {
+                    return self.vault.store(target, secret).map_err(|vault_err| {
+                        format!("{}; key vault fallback also failed: {}", primary_err, vault_err)
+                    });
+                }
+            }
+        }
+
+        self.vault.store(target, secret)
+    }
+
+    fn get(&self, target: &str) -> Result<Option<Zeroizing<String>>, String> {
+        let primary = self.primary.as_ref().map(|p| p.get(target));
+        if let Some(Ok(Some(secret))) = primary {
+            return Ok(Some(secret));
         }
+
+        match (self.vault.get(target)?, primary) {
+            (Some(secret), _) => Ok(Some(secret)),
+            // Not in the vault either, so the OS store's failure is what hid the key
+            (None, Some(Err(e))) => Err(e),
+            (None, _) => Ok(None),
+        }
+    }
+
+    fn delete(&self, target: &str) -> Result<(), String> {
+        let primary_result = match &self.primary {
+            Some(primary) => primary.delete(target),
+            None => Err("No OS credential store".to_string()),
+        };
+        let vault_result = self.vault.delete(target);
+
+        match (primary_result, vault_result) {
+            (Ok(()), _) | (_, Ok(())) => Ok(()),
+            (Err(e), Err(_)) if self.primary.is_some() => Err(e),
+            (_, Err(e)) => Err(e),
+        }
+    }
+}
+
+// The OS credential store when it is usable, backed by the encrypted vault
+pub fn secret_store() -> Result<Box<dyn SecretStore>, String> {
+    let vault_path = app_paths::data_dir()?.join(VAULT_FILE_NAME);
+    Ok(Box::new(FallbackStore {
+        primary: os_secret_store(),
+        vault: EncryptedFileStore::new(vault_path),
+    }))
+}
+
+// The platform keychain, if this OS has one we support and it is reachable
+#[cfg(windows)]
+fn os_secret_store() -> Option<Box<dyn SecretStore>> {
+    let store = CredentialManagerStore;
+    store.is_available().then(|| Box::new(store) as Box<dyn SecretStore>)
+}
+
+#[cfg(target_os = "linux")]
+fn os_secret_store() -> Option<Box<dyn SecretStore>> {
+    let store = SecretServiceStore;
+    store.is_available().then(|| Box::new(store) as Box<dyn SecretStore>)
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn os_secret_store() -> Option<Box<dyn SecretStore>> {
+    None
+}
+
+// Passphrase for the encrypted vault, held in memory for this session only
+pub fn set_vault_passphrase(passphrase: Option<Zeroizing<String>>) {
+    if let Ok(mut current) = VAULT_PASSPHRASE.lock() {
+        *current = passphrase.filter(|p| !p.is_empty());
     }
 }
 
+// Credential ids and metadata live in a plain index file so the secrets themselves
+// never need to be enumerated from the OS store
+
+// Timestamps are seconds since the Unix epoch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CredentialMetadata {
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    // When the current secret was written; rotation resets it
+    pub stored_at: Option<u64>,
+    #[serde(default)]
+    pub last_used_at: Option<u64>,
+    #[serde(default)]
+    pub last_validated_at: Option<u64>,
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialAlertKind {
+    Expired,
+    ExpiresSoon,
+    RotationDue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialAlert {
+    pub credential_id: String,
+    pub kind: CredentialAlertKind,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CredentialIndex {
+    credentials: BTreeMap<String, CredentialMetadata>,
+}
+
+// Where the active key for a credential came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    Environment,
+    File,
+    SecretStore,
+}
+
+// Where the engine config says its credential's key is provisioned. Callers take it from the
+// ConfigState snapshot, so looking up a key never reads (or repairs) the config file.
+#[derive(Debug, Clone, Default)]
+pub struct KeySources {
+    // The credential the env var and file stand in for
+    pub credential_id: String,
+    pub api_key_env: Option<String>,
+    pub api_key_file: Option<String>,
+    // Directory of the credential index that usage and rotation are recorded in; unset is
+    // the app data dir. Tests point it at a scratch directory.
+    pub index_dir: Option<PathBuf>,
+}
+
+impl KeySources {
+    pub fn from_config(config: &EngineConfig) -> Self {
+        KeySources {
+            credential_id: config
+                .credential_id
+                .clone()
+                .unwrap_or_else(|| DEFAULT_CREDENTIAL_ID.to_string()),
+            api_key_env: config.api_key_env.clone(),
+            api_key_file: config.api_key_file.clone(),
+            index_dir: None,
+        }
+    }
+
+    fn index_path(&self) -> Result<PathBuf, String> {
+        match &self.index_dir {
+            Some(dir) => Ok(dir.join(INDEX_FILE_NAME)),
+            None => index_path(),
+        }
+    }
+}
+
+// What the webview may know about a stored key; never the key itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyInfo {
+    pub credential_id: String,
+    pub masked_key: String,
+    pub key_source: KeySource,
+    #[serde(flatten)]
+    pub metadata: CredentialMetadata,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Held around every read-modify-write of the index, so concurrent commands don't drop
+// each other's updates
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_index() -> MutexGuard<'static, ()> {
+    INDEX_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn index_path() -> Result<PathBuf, String> {
+    Ok(app_paths::data_dir()?.join(INDEX_FILE_NAME))
+}
+
+fn read_index(path: &Path) -> Result<CredentialIndex, String> {
+    if !path.exists() {
+        return Ok(CredentialIndex::default());
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read credential index: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse credential index: {}", e))
+}
+
+fn write_index(path: &Path, index: &CredentialIndex) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(index)
+        .map_err(|e| format!("Failed to serialize credential index: {}", e))?;
+    app_paths::write_atomic(path, content.as_bytes(), true)
+        .map_err(|e| format!("Failed to write credential index: {}", e))
+}
+
+// Map a credential id to its target name in the secret store
+// The default id keeps the original target so keys stored by older versions still resolve
+fn credential_target(credential_id: &str) -> Result<String, String> {
+    let valid = !credential_id.is_empty()
+        && credential_id.len() <= 64
+        && credential_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+    if !valid {
+        return Err(format!(
+            "Invalid credential id '{}': use 1-64 letters, digits, '.', '_' or '-'",
+            credential_id
+        ));
+    }
+
+    if credential_id == DEFAULT_CREDENTIAL_ID {
+        Ok(CREDENTIAL_TARGET.to_string())
+    } else {
+        Ok(format!("{}_{}", CREDENTIAL_TARGET, credential_id))
+    }
+}
+
+// Show only the last four characters, and nothing at all for keys too short to hide
+fn mask_key(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return "••••".to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("••••{}", tail)
+}
+
+fn update_metadata(
+    index_path: &Path,
+    credential_id: &str,
+    update: impl FnOnce(&mut CredentialMetadata),
+) -> Result<(), String> {
+    let _guard = lock_index();
+    let mut index = read_index(index_path)?;
+    update(index.credentials.entry(credential_id.to_string()).or_default());
+    write_index(index_path, &index)
+}
+
+pub fn store_api_key(credential_id: &str, key: &str) -> Result<(), String> {
+    let target = credential_target(credential_id)?;
+    secret_store()?.store(&target, key)?;
+
+    // A fresh key starts a fresh lifecycle; rotation keeps created_at instead
+    update_metadata(&index_path()?, credential_id, |meta| {
+        let now = now_secs();
+        *meta = CredentialMetadata {
+            created_at: Some(now),
+            stored_at: Some(now),
+            ..CredentialMetadata::default()
+        };
+    })
+}
+
+// Overwrite the secret in place, so the old key stays until the new one is written
+pub fn rotate_api_key(
+    credential_id: &str,
+    key: &str,
+    expires_at: Option<u64>,
+    sources: &KeySources,
+) -> Result<(), String> {
+    if injected_api_key(credential_id, sources)?.is_some() {
+        return Err(format!(
+            "API key '{}' is provisioned by your deployment and cannot be rotated from the app",
+            credential_id
+        ));
+    }
+
+    let target = credential_target(credential_id)?;
+    secret_store()?.store(&target, key)?;
+
+    update_metadata(&sources.index_path()?, credential_id, |meta| {
+        let now = now_secs();
+        meta.created_at = meta.created_at.or(meta.stored_at).or(Some(now));
+        meta.stored_at = Some(now);
+        meta.last_validated_at = Some(now);
+        meta.expires_at = expires_at;
+    })
+}
+
+pub fn mark_validated(credential_id: &str) -> Result<(), String> {
+    update_metadata(&index_path()?, credential_id, |meta| meta.last_validated_at = Some(now_secs()))
+}
+
+pub fn set_expiry(credential_id: &str, expires_at: Option<u64>) -> Result<(), String> {
+    credential_target(credential_id)?;
+    update_metadata(&index_path()?, credential_id, |meta| meta.expires_at = expires_at)
+}
+
+// Keys provisioned for managed deployments, for the credential the engine config uses
+// Precedence: the environment variable named by `api_key_env` when it is set and non-empty,
+// then the file at `api_key_file`, then the secret store. A configured file that cannot be
+// read is an error rather than a silent fall-through, so a broken mount is noticed.
+fn injected_api_key(
+    credential_id: &str,
+    sources: &KeySources,
+) -> Result<Option<(Zeroizing<String>, KeySource)>, String> {
+    if sources.credential_id != credential_id {
+        return Ok(None);
+    }
+
+    if let Some(var) = &sources.api_key_env {
+        if let Ok(value) = std::env::var(var) {
+            let value = Zeroizing::new(value);
+            if !value.trim().is_empty() {
+                return Ok(Some((Zeroizing::new(value.trim().to_string()), KeySource::Environment)));
+            }
+        }
+    }
+
+    if let Some(path) = &sources.api_key_file {
+        let content = fs::read_to_string(path)
+            .map(Zeroizing::new)
+            .map_err(|e| format!("Failed to read API key file {}: {}", path, e))?;
+        let key = content.trim();
+        if key.is_empty() {
+            return Err(format!("API key file {} is empty", path));
+        }
+        return Ok(Some((Zeroizing::new(key.to_string()), KeySource::File)));
+    }
+
+    Ok(None)
+}
+
+// Ok(None) only when no key is stored or provisioned for the credential. A locked vault, an
+// unreadable key file or an invalid credential id is an error, so callers never tell the
+// user to re-enter a key they already have.
+fn resolve_api_key(
+    credential_id: &str,
+    sources: &KeySources,
+) -> Result<Option<(Zeroizing<String>, KeySource)>, String> {
+    if let Some(injected) = injected_api_key(credential_id, sources)? {
+        return Ok(Some(injected));
+    }
+
+    let key = secret_store()?.get(&credential_target(credential_id)?)?;
+    Ok(key.map(|key| (key, KeySource::SecretStore)))
+}
+
+// Only for Rust-side callers that inject the key into outbound requests
+pub fn get_api_key(
+    credential_id: &str,
+    sources: &KeySources,
+) -> Result<Option<Zeroizing<String>>, String> {
+    Ok(resolve_api_key(credential_id, sources)?.map(|(key, _)| key))
+}
+
+// Like get_api_key, but records that the key was used for a request that spends it.
+// Health checks and model listing should call get_api_key instead.
+pub fn use_api_key(
+    credential_id: &str,
+    sources: &KeySources,
+) -> Result<Option<Zeroizing<String>>, String> {
+    let key = get_api_key(credential_id, sources)?;
+    if key.is_some() {
+        // Failing to record usage must not block the request
+        let _ = record_use(credential_id, sources);
+    }
+    Ok(key)
+}
+
+fn record_use(credential_id: &str, sources: &KeySources) -> Result<(), String> {
+    let path = sources.index_path()?;
+    let _guard = lock_index();
+    let mut index = read_index(&path)?;
+    let now = now_secs();
+    let meta = index.credentials.entry(credential_id.to_string()).or_default();
+    if meta
+        .last_used_at
+        .map(|used| now.saturating_sub(used) < LAST_USED_RESOLUTION_SECS)
+        .unwrap_or(false)
+    {
+        return Ok(());
+    }
+    meta.last_used_at = Some(now);
+    write_index(&path, &index)
+}
+
+pub fn get_api_key_info(credential_id: &str, sources: &KeySources) -> Result<ApiKeyInfo, String> {
+    let (key, key_source) = resolve_api_key(credential_id, sources)?
+        .ok_or(format!("No API key stored as '{}'", credential_id))?;
+    let index_path = sources.index_path()?;
+    let _guard = lock_index();
+    let metadata = read_index(&index_path)?
+        .credentials
+        .remove(credential_id)
+        .unwrap_or_default();
+
+    Ok(ApiKeyInfo {
+        credential_id: credential_id.to_string(),
+        masked_key: mask_key(&key),
+        key_source,
+        metadata,
+    })
+}
+
+pub fn has_api_key(credential_id: &str, sources: &KeySources) -> bool {
+    matches!(get_api_key(credential_id, sources), Ok(Some(_)))
+}
+
+// Only what the secret store holds, ignoring keys provisioned through the config
+fn has_stored_key(credential_id: &str) -> Result<bool, String> {
+    Ok(matches!(secret_store()?.get(&credential_target(credential_id)?), Ok(Some(_))))
+}
+
+pub fn delete_api_key(credential_id: &str) -> Result<(), String> {
+    secret_store()?.delete(&credential_target(credential_id)?)?;
+
+    let index_path = index_path()?;
+    let _guard = lock_index();
+    let mut index = read_index(&index_path)?;
+    if index.credentials.remove(credential_id).is_some() {
+        write_index(&index_path, &index)?;
+    }
+
+    Ok(())
+}
+
+// Ids of stored credentials, never their values
+pub fn list_credential_ids() -> Result<Vec<String>, String> {
+    let index_path = index_path()?;
+    let _guard = lock_index();
+    let mut index = read_index(&index_path)?;
+
+    // A key stored before credentials were indexed only exists under the default id
+    if !index.credentials.contains_key(DEFAULT_CREDENTIAL_ID) && has_stored_key(DEFAULT_CREDENTIAL_ID)? {
+        index
+            .credentials
+            .insert(DEFAULT_CREDENTIAL_ID.to_string(), CredentialMetadata::default());
+        write_index(&index_path, &index)?;
+    }
+
+    Ok(index.credentials.into_keys().collect())
+}
+
+// Expired keys, keys expiring within two weeks, and keys older than the quarterly rotation
+pub fn credential_alerts() -> Vec<CredentialAlert> {
+    let now = now_secs();
+    let index = {
+        let _guard = lock_index();
+        index_path().and_then(|path| read_index(&path)).unwrap_or_default()
+    };
+    let mut alerts = Vec::new();
+
+    for (credential_id, meta) in index.credentials {
+        let days_until = |t: u64| t.saturating_sub(now) / DAY_SECS;
+
+        if let Some(expires_at) = meta.expires_at {
+            if expires_at <= now {
+                alerts.push(CredentialAlert {
+                    credential_id: credential_id.clone(),
+                    kind: CredentialAlertKind::Expired,
+                    message: format!("API key '{}' has expired", credential_id),
+                });
+                continue;
+            }
+            if expires_at - now <= EXPIRY_WARNING_SECS {
+                alerts.push(CredentialAlert {
+                    credential_id: credential_id.clone(),
+                    kind: CredentialAlertKind::ExpiresSoon,
+                    message: format!(
+                        "API key '{}' expires in {} day(s)",
+                        credential_id,
+                        days_until(expires_at)
+                    ),
+                });
+                continue;
+            }
+        }
+
+        if let Some(stored_at) = meta.stored_at {
+            if now.saturating_sub(stored_at) >= ROTATION_INTERVAL_SECS {
+                alerts.push(CredentialAlert {
+                    credential_id: credential_id.clone(),
+                    kind: CredentialAlertKind::RotationDue,
+                    message: format!(
+                        "API key '{}' has not been rotated in {} days",
+                        credential_id,
+                        now.saturating_sub(stored_at) / DAY_SECS
+                    ),
+                });
+            }
+        }
+    }
+
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_sources(name: &str, content: &str) -> KeySources {
+        let path = std::env::temp_dir().join(format!("dataconfessional-{}-{}", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        KeySources {
+            credential_id: DEFAULT_CREDENTIAL_ID.to_string(),
+            api_key_env: None,
+            api_key_file: Some(path.display().to_string()),
+            index_dir: None,
+        }
+    }
+
+    #[test]
+    fn key_file_from_the_snapshot_is_used_for_its_credential() {
+        let sources = file_sources("key-file", "sk-from-file\n");
+
+        let (key, source) = injected_api_key(DEFAULT_CREDENTIAL_ID, &sources).unwrap().unwrap();
+        assert_eq!(key.as_str(), "sk-from-file");
+        assert_eq!(source, KeySource::File);
+
+        // Other credentials still come from the secret store
+        assert!(injected_api_key("other", &sources).unwrap().is_none());
+    }
+
+    #[test]
+    fn unreadable_key_file_is_an_error() {
+        let mut sources = file_sources("empty-key-file", "  \n");
+        assert!(injected_api_key(DEFAULT_CREDENTIAL_ID, &sources).is_err());
+
+        sources.api_key_file = Some("/nonexistent/dataconfessional/key".to_string());
+        assert!(injected_api_key(DEFAULT_CREDENTIAL_ID, &sources).is_err());
+    }
+
+    #[test]
+    fn vault_is_keyed_by_a_private_per_install_secret() {
+        let dir = std::env::temp_dir().join(format!("dataconfessional-vault-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let store = EncryptedFileStore::new(dir.join(VAULT_FILE_NAME));
+
+        store.store("default", "sk-in-vault").unwrap();
+        assert_eq!(store.get("default").unwrap().unwrap().as_str(), "sk-in-vault");
+        assert!(store.get("other").unwrap().is_none());
+
+        let secret_path = dir.join(INSTALL_SECRET_FILE_NAME);
+        assert_eq!(fs::read(&secret_path).unwrap().len(), 32);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&secret_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Without its secret the vault stays shut
+        fs::remove_file(&secret_path).unwrap();
+        assert!(store.get("default").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
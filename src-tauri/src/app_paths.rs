@@ -0,0 +1,188 @@
+// App data locations shared by every module that persists files
+// Windows: %APPDATA%\DataConfessional, macOS: ~/Library/Application Support/DataConfessional,
+// Linux: $XDG_CONFIG_HOME/DataConfessional for config and $XDG_DATA_HOME/DataConfessional for data.
+// A portable install can put everything under one directory with --data-dir or DATACONFESSIONAL_HOME.
+// The organization policy file lives in a system-wide location users cannot write to.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const APP_DIR_NAME: &str = "DataConfessional";
+const HOME_ENV_VAR: &str = "DATACONFESSIONAL_HOME";
+const DATA_DIR_FLAG: &str = "--data-dir";
+const POLICY_FILE_NAME: &str = "engine-policy.json";
+#[cfg(debug_assertions)]
+const POLICY_ENV_VAR: &str = "DATACONFESSIONAL_POLICY_FILE";
+
+static DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+// Pick up `--data-dir <path>` or `--data-dir=<path>` from the command line
+// Call once at startup, before anything reads or writes app data
+pub fn init_from_args<I: IntoIterator<Item = String>>(args: I) {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = if arg == DATA_DIR_FLAG {
+            args.next()
+        } else {
+            arg.strip_prefix(&format!("{}=", DATA_DIR_FLAG)).map(str::to_string)
+        };
+
+        if let Some(dir) = value.filter(|d| !d.is_empty()) {
+            let _ = DIR_OVERRIDE.set(PathBuf::from(dir));
+            return;
+        }
+    }
+}
+
+// The command-line flag wins over the environment variable
+fn override_dir() -> Option<PathBuf> {
+    DIR_OVERRIDE.get().cloned().or_else(|| {
+        std::env::var_os(HOME_ENV_VAR)
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
+#[cfg(unix)]
+fn home_dir() -> Result<PathBuf, String> {
+    std::env::var_os("HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .ok_or("Failed to get home directory".to_string())
+}
+
+// XDG variables must hold absolute paths to be honored
+#[cfg(all(unix, not(target_os = "macos")))]
+fn xdg_dir(var: &str, fallback: &str) -> Result<PathBuf, String> {
+    match std::env::var_os(var).map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => Ok(dir),
+        _ => Ok(home_dir()?.join(fallback)),
+    }
+}
+
+#[cfg(windows)]
+fn platform_config_base() -> Result<PathBuf, String> {
+    std::env::var_os("APPDATA")
+        .or_else(|| std::env::var_os("LOCALAPPDATA"))
+        .map(PathBuf::from)
+        .ok_or("Failed to get app data directory".to_string())
+}
+
+#[cfg(windows)]
+fn platform_data_base() -> Result<PathBuf, String> {
+    platform_config_base()
+}
+
+#[cfg(target_os = "macos")]
+fn platform_config_base() -> Result<PathBuf, String> {
+    Ok(home_dir()?.join("Library").join("Application Support"))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_data_base() -> Result<PathBuf, String> {
+    platform_config_base()
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_config_base() -> Result<PathBuf, String> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_data_base() -> Result<PathBuf, String> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+// Replace `path` through a synced temp file and a rename, so a crash leaves either the old
+// file or the new one. Private files are created owner-only (0600 on Unix) from the start.
+pub fn write_atomic(path: &Path, content: &[u8], private: bool) -> io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+
+    {
+        let mut file = options.open(&tmp_path)?;
+        // The mode only applies on creation; tighten a temp file left over from a crash
+        #[cfg(unix)]
+        if private {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(content)?;
+        file.sync_all()?;
+    }
+
+    fs::rename(&tmp_path, path)?;
+
+    // Persist the rename itself
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+fn ensure_dir(dir: PathBuf) -> Result<PathBuf, String> {
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create app data directory {}: {}", dir.display(), e))?;
+    }
+    Ok(dir)
+}
+
+// Settings such as engine-config.json
+pub fn config_dir() -> Result<PathBuf, String> {
+    match override_dir() {
+        Some(dir) => ensure_dir(dir),
+        None => ensure_dir(platform_config_base()?.join(APP_DIR_NAME)),
+    }
+}
+
+// Everything else the app persists: key vault, credential index
+pub fn data_dir() -> Result<PathBuf, String> {
+    match override_dir() {
+        Some(dir) => ensure_dir(dir),
+        None => ensure_dir(platform_data_base()?.join(APP_DIR_NAME)),
+    }
+}
+
+#[cfg(windows)]
+fn platform_policy_base() -> Option<PathBuf> {
+    std::env::var_os("ProgramData").map(PathBuf::from)
+}
+
+#[cfg(target_os = "macos")]
+fn platform_policy_base() -> Option<PathBuf> {
+    Some(PathBuf::from("/Library/Application Support"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_policy_base() -> Option<PathBuf> {
+    Some(PathBuf::from("/etc"))
+}
+
+// Organization-managed policy. Never moved by --data-dir or DATACONFESSIONAL_HOME, since
+// those are under the user's control; debug builds can point elsewhere for testing.
+pub fn policy_path() -> Option<PathBuf> {
+    #[cfg(debug_assertions)]
+    if let Some(path) = std::env::var_os(POLICY_ENV_VAR).filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+
+    platform_policy_base().map(|base| base.join(APP_DIR_NAME).join(POLICY_FILE_NAME))
+}
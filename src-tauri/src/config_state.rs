@@ -0,0 +1,184 @@
+// In-memory engine config shared by every command through Tauri managed state
+// Watches engine-config.json so edits made outside the app are validated and picked up live
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::sync::mpsc;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::engine_config::{self, EngineConfig};
+use crate::policy;
+
+pub const CONFIG_CHANGED_EVENT: &str = "engine_config_changed";
+pub const CONFIG_REJECTED_EVENT: &str = "engine_config_rejected";
+
+// Editors often write a file in several steps; wait for it to settle before reading
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigChangeSource {
+    // A command in this app changed it
+    App,
+    // engine-config.json was edited outside the app
+    File,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChangedEvent {
+    pub source: ConfigChangeSource,
+    pub config: EngineConfig,
+    // Fields the organization policy manages
+    pub locked_fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigRejectedEvent {
+    pub error: String,
+}
+
+#[derive(Default)]
+pub struct ConfigState {
+    // Loaded on first use so a broken file surfaces as a command error, as before
+    config: RwLock<Option<EngineConfig>>,
+    app: Mutex<Option<AppHandle>>,
+    // Dropping the watcher stops it, so it lives as long as the state
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl ConfigState {
+    // Snapshot of the current config
+    pub fn get(&self) -> Result<EngineConfig, String> {
+        if let Some(config) = self.config.read().map_err(|_| lock_error())?.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let mut current = self.config.write().map_err(|_| lock_error())?;
+        if current.is_none() {
+            *current = Some(engine_config::load_config()?);
+        }
+        Ok(current.clone().unwrap_or_default())
+    }
+
+    // Apply `change`, validate and save it, and publish it. Nothing is kept if any step fails.
+    pub fn update<F>(&self, change: F) -> Result<EngineConfig, String>
+    where
+        F: FnOnce(&mut EngineConfig) -> Result<(), String>,
+    {
+        let mut current = self.config.write().map_err(|_| lock_error())?;
+        let mut config = match current.as_ref() {
+            Some(config) => config.clone(),
+            None => engine_config::load_config()?,
+        };
+
+        change(&mut config)?;
+        engine_config::validate_config(&config)?;
+        engine_config::save_config(&config)?;
+        *current = Some(config.clone());
+        drop(current);
+
+        self.emit(
+            CONFIG_CHANGED_EVENT,
+            ConfigChangedEvent {
+                source: ConfigChangeSource::App,
+                config: config.clone(),
+                locked_fields: current_locked_fields(),
+            },
+        );
+        Ok(config)
+    }
+
+    // Start watching engine-config.json. The directory is watched rather than the file
+    // because saves replace the file by renaming over it.
+    pub fn watch(&self, app: AppHandle) -> Result<(), String> {
+        let config_path = engine_config::get_config_path()?;
+        let config_dir = config_path
+            .parent()
+            .ok_or("Failed to get config directory".to_string())?
+            .to_path_buf();
+
+        let (tx, rx) = mpsc::channel();
+        let watched_path = config_path.clone();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if let Ok(event) = res {
+                if event.paths.iter().any(|p| p == &watched_path) {
+                    let _ = tx.send(());
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to watch config file: {}", e))?;
+
+        watcher
+            .watch(&config_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch config file: {}", e))?;
+
+        *self.watcher.lock().map_err(|_| lock_error())? = Some(watcher);
+        *self.app.lock().map_err(|_| lock_error())? = Some(app.clone());
+
+        std::thread::spawn(move || {
+            while rx.recv().is_ok() {
+                while rx.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+                app.state::<ConfigState>().reload_from_disk(&config_path);
+            }
+        });
+
+        Ok(())
+    }
+
+    // Pick up an external edit. An edit that does not load or validate is rejected and the
+    // previous config stays in effect; the file is left alone so the user can fix it.
+    fn reload_from_disk(&self, config_path: &std::path::Path) {
+        // Deleted or mid-rename: the next save writes it again
+        if !config_path.exists() {
+            return;
+        }
+
+        let loaded = match engine_config::read_config(config_path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Ignoring invalid edit to engine-config.json: {}", e);
+                self.emit(CONFIG_REJECTED_EVENT, ConfigRejectedEvent { error: e });
+                return;
+            }
+        };
+
+        let mut current = match self.config.write() {
+            Ok(current) => current,
+            Err(_) => return,
+        };
+        // Our own saves land here too
+        if current.as_ref() == Some(&loaded) {
+            return;
+        }
+        *current = Some(loaded.clone());
+        drop(current);
+
+        self.emit(
+            CONFIG_CHANGED_EVENT,
+            ConfigChangedEvent {
+                source: ConfigChangeSource::File,
+                config: loaded,
+                locked_fields: current_locked_fields(),
+            },
+        );
+    }
+
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Ok(app) = self.app.lock() {
+            if let Some(app) = app.as_ref() {
+                let _ = app.emit_all(event, payload);
+            }
+        }
+    }
+}
+
+fn current_locked_fields() -> Vec<String> {
+    let policy = policy::load_policy().ok().flatten();
+    policy::locked_fields(policy.as_ref())
+}
+
+fn lock_error() -> String {
+    "Failed to access engine config: lock poisoned".to_string()
+}
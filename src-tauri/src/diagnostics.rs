@@ -0,0 +1,348 @@
+// Engine diagnostics report for support requests
+// Plain text the user can copy into a ticket; URLs, keys and the home directory are redacted
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::time::Instant;
+
+use crate::api_key::KeySources;
+use crate::engine::EngineHealth;
+use crate::engine_config::{self, EngineConfig, ProviderConfig, PACK_ROLES, PRIMARY_PROVIDER_ID};
+use crate::policy::{self, EnginePolicy};
+use crate::provider;
+use crate::system_info::{bytes_to_gb, HardwareInfo};
+
+// Plain letters so it survives inside URLs without percent-encoding
+const REDACTED: &str = "REDACTED";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineDiagnostics {
+    // The copyable report
+    pub report: String,
+    pub health: EngineHealth,
+    pub hardware: HardwareInfo,
+    pub fallback_providers: Vec<ProviderStatus>,
+}
+
+// How a fallback provider answered when the report was made
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderStatus {
+    pub provider: ProviderConfig,
+    pub available: bool,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+// Health-check one provider the way collect_health does the primary
+pub async fn probe_provider(entry: &ProviderConfig, key_sources: &KeySources) -> ProviderStatus {
+    let started = Instant::now();
+    let result = match policy::check_provider(&entry.provider, &entry.base_url) {
+        Ok(()) => match provider::provider_for(entry, key_sources) {
+            Ok(provider) => provider.health().await,
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+
+    ProviderStatus {
+        provider: redact_provider(entry),
+        available: result.is_ok(),
+        latency_ms: result.is_ok().then(|| started.elapsed().as_millis() as u64),
+        error: result.err().map(|e| redact_text(&e)),
+    }
+}
+
+pub fn build_report(
+    config: &EngineConfig,
+    health: &EngineHealth,
+    hardware: &HardwareInfo,
+    policy: &Result<Option<EnginePolicy>, String>,
+    fallbacks: &[ProviderStatus],
+) -> String {
+    let mut out = String::new();
+    // Writing to a String cannot fail
+    let _ = write_report(&mut out, config, health, hardware, policy, fallbacks);
+    redact_text(&out)
+}
+
+fn write_report(
+    out: &mut String,
+    config: &EngineConfig,
+    health: &EngineHealth,
+    hardware: &HardwareInfo,
+    policy: &Result<Option<EnginePolicy>, String>,
+    fallbacks: &[ProviderStatus],
+) -> std::fmt::Result {
+    writeln!(out, "DataConfessional engine diagnostics")?;
+    writeln!(out, "App version: {}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(out, "Platform: {} {}", std::env::consts::OS, std::env::consts::ARCH)?;
+
+    writeln!(out, "\n[Config]")?;
+    writeln!(out, "Path: {}", or_unknown(health.config_path.as_deref()))?;
+    writeln!(out, "Schema version: {}", config.schema_version)?;
+    if let Some(notice) = &health.config_recovery {
+        writeln!(out, "Recovery: {}", notice)?;
+    }
+    match policy {
+        Ok(Some(policy)) => {
+            let locked = policy::locked_fields(Some(policy));
+            writeln!(out, "Organization policy: in force (locked: {})", list_or_none(&locked))?;
+        }
+        Ok(None) => writeln!(out, "Organization policy: none")?,
+        Err(e) => writeln!(out, "Organization policy: unreadable ({})", e)?,
+    }
+    if let Some(error) = &health.policy_error {
+        writeln!(out, "Policy error: {}", error)?;
+    }
+
+    writeln!(out, "\n[Provider]")?;
+    match &health.provider {
+        Some(provider) => write_provider(out, provider)?,
+        None => writeln!(out, "Provider: unknown")?,
+    }
+    writeln!(out, "Available: {}", yes_no(health.ollama_available))?;
+    if let Some(error) = &health.provider_error {
+        writeln!(out, "Error: {}", error)?;
+    }
+    writeln!(out, "Server version: {}", or_unknown(health.ollama_version.as_deref()))?;
+    match health.latency_ms {
+        Some(ms) => writeln!(out, "Latency: {} ms", ms)?,
+        None => writeln!(out, "Latency: n/a")?,
+    }
+    for status in fallbacks {
+        writeln!(out, "Fallback:")?;
+        write_provider(out, &status.provider)?;
+        match (status.available, &status.error, status.latency_ms) {
+            (true, _, Some(ms)) => writeln!(out, "  Available: yes ({} ms)", ms)?,
+            (_, Some(error), _) => writeln!(out, "  Available: no ({})", error)?,
+            _ => writeln!(out, "  Available: {}", yes_no(status.available))?,
+        }
+    }
+
+    writeln!(out, "\n[Models]")?;
+    writeln!(out, "Active pack: {}", or_unknown(health.active_pack_id.as_deref()))?;
+    if let Some(pack) = engine_config::get_active_pack(config) {
+        for role in PACK_ROLES {
+            if let Ok(role_model) = pack.role(role) {
+                writeln!(
+                    out,
+                    "  {}: {} on {}",
+                    role,
+                    role_model.model,
+                    role_model.provider_id.as_deref().unwrap_or(PRIMARY_PROVIDER_ID)
+                )?;
+            }
+        }
+    }
+    writeln!(out, "Engine configured: {}", yes_no(health.engine_configured))?;
+    writeln!(out, "Missing: {}", list_or_none(&health.missing_models))?;
+    if health.loaded_models.is_empty() {
+        writeln!(out, "Loaded in memory: none")?;
+    }
+    for model in &health.loaded_models {
+        let size = model.size_bytes.map(bytes_to_gb);
+        let vram = model.vram_bytes.map(bytes_to_gb);
+        match (size, vram) {
+            (Some(size), Some(vram)) => {
+                writeln!(out, "Loaded in memory: {} ({} GB, {} GB in GPU memory)", model.name, size, vram)?
+            }
+            (Some(size), None) => writeln!(out, "Loaded in memory: {} ({} GB)", model.name, size)?,
+            _ => writeln!(out, "Loaded in memory: {}", model.name)?,
+        }
+    }
+    writeln!(out, "Model directory: {}", or_unknown(health.models_dir.as_deref()))?;
+    match health.free_disk_gb {
+        Some(free) => writeln!(out, "Free disk: {} GB", free)?,
+        None => writeln!(out, "Free disk: unknown")?,
+    }
+
+    writeln!(out, "\n[Hardware]")?;
+    match (hardware.total_ram_gb, hardware.available_ram_gb) {
+        (Some(total), Some(available)) => writeln!(out, "RAM: {} GB ({} GB available)", total, available)?,
+        (Some(total), None) => writeln!(out, "RAM: {} GB", total)?,
+        _ => writeln!(out, "RAM: unknown")?,
+    }
+    writeln!(
+        out,
+        "CPU: {}, {} cores, features: {}",
+        hardware.cpu_arch,
+        hardware.cpu_cores,
+        list_or_none(&hardware.cpu_features)
+    )?;
+    writeln!(out, "GPU: {}", hardware.gpu.description)?;
+    for gpu in &hardware.gpu.gpus {
+        writeln!(
+            out,
+            "  {} {} (VRAM: {}, driver: {} {})",
+            gpu.vendor,
+            or_unknown(gpu.name.as_deref()),
+            gpu.vram_mb.map(|mb| format!("{} MB", mb)).unwrap_or_else(|| "n/a".to_string()),
+            or_unknown(gpu.driver.as_deref()),
+            gpu.driver_version.as_deref().unwrap_or("")
+        )?;
+    }
+
+    writeln!(out, "\n[Credentials]")?;
+    if health.credential_alerts.is_empty() {
+        writeln!(out, "No alerts")?;
+    }
+    for alert in &health.credential_alerts {
+        writeln!(out, "{}: {}", alert.credential_id, alert.message)?;
+    }
+
+    Ok(())
+}
+
+fn write_provider(out: &mut String, provider: &ProviderConfig) -> std::fmt::Result {
+    writeln!(out, "  Id: {}", provider.id)?;
+    writeln!(out, "  Type: {}", provider.provider)?;
+    writeln!(out, "  Base URL: {}", redact_url(&provider.base_url))?;
+    // The credential id names the stored key; the key itself never leaves the store
+    writeln!(out, "  Credential: {}", or_unknown(provider.credential_id.as_deref()))?;
+    if let Some(model) = &provider.model {
+        writeln!(out, "  Model override: {}", model)?;
+    }
+    Ok(())
+}
+
+pub fn redact_provider(provider: &ProviderConfig) -> ProviderConfig {
+    ProviderConfig {
+        base_url: redact_url(&provider.base_url),
+        ..provider.clone()
+    }
+}
+
+// Keep scheme, host, port and path; drop user info and query values, which can carry tokens
+pub fn redact_url(url: &str) -> String {
+    let mut parsed = match reqwest::Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(_) => return url.to_string(),
+    };
+
+    let has_user_info = !parsed.username().is_empty() || parsed.password().is_some();
+    if !has_user_info && parsed.query().is_none() {
+        return url.to_string();
+    }
+
+    if has_user_info {
+        let _ = parsed.set_username(REDACTED);
+        let _ = parsed.set_password(None);
+    }
+    if parsed.query().is_some() {
+        let keys: Vec<String> = parsed.query_pairs().map(|(key, _)| key.to_string()).collect();
+        parsed.query_pairs_mut().clear();
+        for key in keys {
+            parsed.query_pairs_mut().append_pair(&key, REDACTED);
+        }
+    }
+
+    parsed.to_string()
+}
+
+// Last line of defense over the whole report: URLs with credentials, anything shaped like
+// an API key or bearer token, and the user's home directory
+pub fn redact_text(text: &str) -> String {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+        .filter(|home| home.len() > 1);
+
+    text.lines()
+        .map(|line| {
+            let mut after_bearer = false;
+            let line = line
+                .split(' ')
+                .map(|word| {
+                    let redacted = if after_bearer {
+                        REDACTED.to_string()
+                    } else if word.contains("://") {
+                        redact_url_in(word)
+                    } else if looks_like_key(word) {
+                        REDACTED.to_string()
+                    } else {
+                        word.to_string()
+                    };
+                    after_bearer = word.eq_ignore_ascii_case("bearer");
+                    redacted
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            match &home {
+                Some(home) => line.replace(home.as_str(), "~"),
+                None => line,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// A URL inside an error message, possibly wrapped in brackets or followed by a colon
+fn redact_url_in(word: &str) -> String {
+    let start = word.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(0);
+    let end = word
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !matches!(c, ')' | ']' | ',' | ':' | ';' | '.' | '"' | '\''))
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(word.len())
+        .max(start);
+    format!("{}{}{}", &word[..start], redact_url(&word[start..end]), &word[end..])
+}
+
+// Anthropic and OpenAI style keys, or key=value pairs naming a secret
+fn looks_like_key(word: &str) -> bool {
+    let word = word.trim_matches(|c: char| matches!(c, '"' | '\'' | ',' | '(' | ')'));
+    let lower = word.to_ascii_lowercase();
+    (word.starts_with("sk-") && word.len() > 12)
+        || ["api_key=", "apikey=", "token=", "password=", "secret="]
+            .iter()
+            .any(|prefix| lower.starts_with(prefix))
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+fn or_unknown(value: Option<&str>) -> &str {
+    value.unwrap_or("unknown")
+}
+
+fn list_or_none(items: &[String]) -> String {
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_ending_in_non_ascii_keeps_its_closing_bracket() {
+        let redacted = redact_text("failed (http://host/é)");
+        assert!(redacted.starts_with("failed (http://host/"));
+        assert!(redacted.ends_with(')'));
+    }
+
+    #[test]
+    fn query_values_and_user_info_are_redacted() {
+        let redacted = redact_text("GET https://user:pw@host/v1?token=abc&key=def: timed out");
+        assert!(!redacted.contains("pw"));
+        assert!(!redacted.contains("abc"));
+        assert!(!redacted.contains("def"));
+        assert!(redacted.ends_with(": timed out"));
+    }
+
+    #[test]
+    fn keys_and_bearer_tokens_are_redacted() {
+        let redacted = redact_text("Authorization: Bearer abc123 with sk-ant-0123456789abcdef");
+        assert!(!redacted.contains("abc123"));
+        assert!(!redacted.contains("sk-ant-0123456789abcdef"));
+    }
+}
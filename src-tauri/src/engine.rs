@@ -1,14 +1,21 @@
-// Engine module for Ollama integration
-// Handles health checks, model pack installation, and chat/report generation
-
-use crate::engine_config::{self, EngineConfig, ModelPack};
+// Engine module: Tauri commands for the local analysis engine
+// Handles health checks, model pack installation, and chat/report generation through the configured provider
+
+use crate::anthropic::{AnthropicProvider, ANTHROPIC_BASE_URL};
+use crate::api_key::{self, KeySources};
+use crate::config_state::ConfigState;
+use crate::diagnostics::{self, EngineDiagnostics};
+use crate::engine_config::{self, EngineConfig, ModelPack, ProviderConfig, RoleModel, PRIMARY_PROVIDER_ID};
+use crate::gpu::{self, GpuSummary};
+use crate::policy::{self, EnginePolicy};
+use crate::provider::{self, ChatMessage, CompletionRequest, EngineProvider, LoadedModel};
+use crate::recommend::{self, PackRecommendations};
+use crate::system_info;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::time::Duration;
-use tauri::Window;
-use futures_util::StreamExt;
-
-const OLLAMA_TIMEOUT: Duration = Duration::from_secs(5);
+use std::collections::BTreeMap;
+use std::time::Instant;
+use tauri::{State, Window};
+use zeroize::Zeroizing;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EngineHealth {
@@ -17,12 +24,32 @@ pub struct EngineHealth {
     pub active_pack_id: Option<String>,
     pub missing_models: Vec<String>,
     pub gpu_summary: Option<GpuSummary>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GpuSummary {
-    pub vendor: String,
-    pub vram_gb: Option<u32>,
+    #[serde(default)]
+    pub credential_alerts: Vec<api_key::CredentialAlert>,
+    pub config_recovery: Option<String>,
+    // Set when the organization policy rules out the configured provider
+    #[serde(default)]
+    pub policy_error: Option<String>,
+    // Why the provider is unavailable: an unknown provider type, or its health check failing
+    #[serde(default)]
+    pub provider_error: Option<String>,
+    // The provider this health check was run against
+    #[serde(default)]
+    pub provider: Option<ProviderConfig>,
+    #[serde(default)]
+    pub ollama_version: Option<String>,
+    // Round trip of the health request
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    #[serde(default)]
+    pub loaded_models: Vec<LoadedModel>,
+    // Only set when the provider keeps its models on this machine
+    #[serde(default)]
+    pub models_dir: Option<String>,
+    #[serde(default)]
+    pub free_disk_gb: Option<f64>,
+    #[serde(default)]
+    pub config_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,107 +77,113 @@ pub struct EngineReportRequest {
 pub struct EngineReportResponse {
     pub markdown: String,
     pub model_name: String,
+    // Which entry of the provider chain wrote the report
+    pub provider_id: String,
+    pub provider: String,
 }
 
-// Check if Ollama is available
-async fn check_ollama_available(base_url: &str) -> bool {
-    let client = reqwest::Client::builder()
-        .timeout(OLLAMA_TIMEOUT)
-        .build();
-
-    match client {
-        Ok(c) => {
-            match c.get(format!("{}/api/tags", base_url)).send().await {
-                Ok(resp) => resp.status().is_success(),
-                Err(_) => false,
-            }
-        }
-        Err(_) => false,
-    }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineChatResponse {
+    pub content: String,
+    pub model_name: String,
+    pub provider_id: String,
+    pub provider: String,
 }
 
-// Get list of installed models from Ollama
-async fn get_installed_models(base_url: &str) -> Result<Vec<String>, String> {
-    let client = reqwest::Client::builder()
-        .timeout(OLLAMA_TIMEOUT)
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    let response = client
-        .get(format!("{}/api/tags", base_url))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("Ollama returned status: {}", response.status()));
-    }
-
-    let json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
-
-    let models = json
-        .get("models")
-        .and_then(|m| m.as_array())
-        .ok_or("Invalid response format from Ollama")?;
+// Payload of engine_chat_chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineChatChunk {
+    pub content: String,
+    pub provider_id: String,
+}
 
-    let model_names: Vec<String> = models
-        .iter()
-        .filter_map(|m| {
-            m.get("name").and_then(|n| n.as_str()).map(|s| s.to_string())
-        })
-        .collect();
+// Payload of engine_chat_done
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineChatDone {
+    pub model_name: String,
+    pub provider_id: String,
+    pub provider: String,
+}
 
-    Ok(model_names)
+// Models a provider in the chain can serve, if the policy lets us ask it
+async fn installed_models(config: &EngineConfig, provider_id: &str) -> Result<Vec<String>, String> {
+    let entry = config.provider_by_id(provider_id)?;
+    policy::check_provider(&entry.provider, &entry.base_url)?;
+    provider::provider_for(&entry, &KeySources::from_config(config))?.list_models().await
 }
 
-// Check which models are missing from a pack
-fn check_missing_models(pack: &ModelPack, installed: &[String]) -> Vec<String> {
+// Check which models are missing from a pack, each on the provider its role is routed to.
+// The flag is false when some provider could not be asked; all of its models count as missing.
+async fn check_missing_models(config: &EngineConfig, pack: &ModelPack) -> (Vec<String>, bool) {
     let mut missing = Vec::new();
+    let mut all_known = true;
 
-    let required = vec![
-        pack.analysis_model.clone(),
-        pack.report_model.clone(),
-        pack.embedding_model.clone(),
-    ];
-
-    for model in required {
-        if !installed.contains(&model) {
-            missing.push(model);
+    for (provider_id, models) in pack.models_by_provider() {
+        let installed = installed_models(config, &provider_id).await.unwrap_or_else(|_| {
+            all_known = false;
+            Vec::new()
+        });
+        for model in models {
+            if !installed.contains(&model) && !missing.contains(&model) {
+                missing.push(model);
+            }
         }
     }
 
-    missing
+    (missing, all_known)
 }
 
 #[tauri::command]
-pub async fn engine_health() -> Result<EngineHealth, String> {
-    let config = engine_config::load_config()?;
-    let base_url = &config.base_url;
+pub async fn engine_health(state: State<'_, ConfigState>) -> Result<EngineHealth, String> {
+    let config = state.get()?;
+    collect_health(&config).await
+}
 
-    let ollama_available = check_ollama_available(base_url).await;
+async fn collect_health(config: &EngineConfig) -> Result<EngineHealth, String> {
+    let primary = config.primary_provider();
+    // A provider that cannot be built is reported as unavailable rather than failing the check
+    let provider = provider::provider_for(&primary, &KeySources::from_config(config));
+
+    // Never contact a host the policy rules out, not even for a health check
+    let policy_error = policy::check_config(config).err();
+    let started = Instant::now();
+    let provider_error = match &provider {
+        _ if policy_error.is_some() => None,
+        Ok(provider) => provider.health().await.err(),
+        Err(e) => Some(e.clone()),
+    };
+    let ollama_available = policy_error.is_none() && provider_error.is_none();
+    let latency_ms = ollama_available.then(|| started.elapsed().as_millis() as u64);
+
+    let mut ollama_version = None;
+    let mut loaded_models = Vec::new();
+    if let (true, Ok(provider)) = (ollama_available, &provider) {
+        ollama_version = provider.version().await.ok().flatten();
+        loaded_models = provider.loaded_models().await.unwrap_or_default();
+    }
+
+    let models_dir = if recommend::runs_on_this_machine(&primary) {
+        system_info::ollama_models_dir()
+    } else {
+        None
+    };
+    let free_disk_gb = models_dir
+        .as_deref()
+        .and_then(system_info::free_disk_bytes)
+        .map(system_info::bytes_to_gb);
 
     let mut engine_configured = false;
     let mut missing_models = Vec::new();
 
     if ollama_available {
-        let installed = get_installed_models(base_url).await.unwrap_or_default();
-
-        if let Some(pack_id) = &config.active_pack_id {
-            if let Some(pack) = engine_config::get_active_pack(&config) {
-                missing_models = check_missing_models(pack, &installed);
-                engine_configured = missing_models.is_empty();
-            }
+        if let Some(pack) = engine_config::get_active_pack(config) {
+            missing_models = check_missing_models(config, pack).await.0;
+            engine_configured = missing_models.is_empty();
         }
     }
 
-    // Basic GPU detection (simplified - can be enhanced later)
-    let gpu_summary = Some(GpuSummary {
-        vendor: "unknown".to_string(),
-        vram_gb: None,
-    });
+    // Reads sysfs and may run nvidia-smi, so keep it off the async runtime
+    let gpu_summary = tauri::async_runtime::spawn_blocking(gpu::detect_gpus).await.ok();
 
     Ok(EngineHealth {
         ollama_available,
@@ -158,80 +191,547 @@ pub async fn engine_health() -> Result<EngineHealth, String> {
         active_pack_id: config.active_pack_id.clone(),
         missing_models,
         gpu_summary,
+        credential_alerts: api_key::credential_alerts(),
+        config_recovery: engine_config::config_recovery_notice(),
+        policy_error,
+        provider_error,
+        provider: Some(primary),
+        ollama_version,
+        latency_ms,
+        loaded_models,
+        models_dir: models_dir.map(|dir| dir.display().to_string()),
+        free_disk_gb,
+        config_path: engine_config::get_config_path()
+            .ok()
+            .map(|path| path.display().to_string()),
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedConfig {
+    pub config: EngineConfig,
+    // The organization policy in force, if any
+    pub policy: Option<EnginePolicy>,
+    // Config fields the policy manages; show these read-only
+    pub locked_fields: Vec<String>,
+}
+
+// Current config as the backend sees it; engine_config_changed carries later updates
+#[tauri::command]
+pub async fn engine_get_config(state: State<'_, ConfigState>) -> Result<ManagedConfig, String> {
+    let config = state.get()?;
+    let policy = policy::load_policy()?;
+    let locked_fields = policy::locked_fields(policy.as_ref());
+
+    Ok(ManagedConfig {
+        config,
+        policy,
+        locked_fields,
+    })
+}
+
+// Health, hardware and every provider in the chain in one report for support, secrets redacted
+#[tauri::command]
+pub async fn engine_diagnostics(state: State<'_, ConfigState>) -> Result<EngineDiagnostics, String> {
+    let config = state.get()?;
+    let mut health = collect_health(&config).await?;
+    health.provider = health.provider.as_ref().map(diagnostics::redact_provider);
+
+    let hardware = tauri::async_runtime::spawn_blocking(system_info::detect_hardware)
+        .await
+        .map_err(|e| format!("Failed to detect hardware: {}", e))?;
+
+    let key_sources = KeySources::from_config(&config);
+    let mut fallback_providers = Vec::new();
+    for entry in &config.fallback_providers {
+        fallback_providers.push(diagnostics::probe_provider(entry, &key_sources).await);
+    }
+
+    let policy = policy::load_policy();
+    let report = diagnostics::build_report(&config, &health, &hardware, &policy, &fallback_providers);
+
+    Ok(EngineDiagnostics {
+        report,
+        health,
+        hardware,
+        fallback_providers,
     })
 }
 
+// Which packs this machine can run well, best first, with the reasons for each
 #[tauri::command]
-pub async fn engine_install_pack(pack_id: String) -> Result<EngineHealth, String> {
-    let mut config = engine_config::load_config()?;
-    let base_url = &config.base_url;
+pub async fn engine_recommend_pack(state: State<'_, ConfigState>) -> Result<PackRecommendations, String> {
+    let config = state.get()?;
+    let policy = policy::load_policy()?;
 
-    // Check if Ollama is available
-    if !check_ollama_available(base_url).await {
-        return Err("Ollama is not available. Please install and start Ollama first.".to_string());
+    // Reads sysfs and may run nvidia-smi, so keep it off the async runtime
+    let hardware = tauri::async_runtime::spawn_blocking(system_info::detect_hardware)
+        .await
+        .map_err(|e| format!("Failed to detect hardware: {}", e))?;
+
+    // Sizes of what local providers already have; an unreachable one just means nothing installed
+    let key_sources = KeySources::from_config(&config);
+    let mut installed = BTreeMap::new();
+    for entry in config.provider_chain() {
+        if !recommend::runs_on_this_machine(&entry) {
+            continue;
+        }
+        if let Some(policy) = &policy {
+            if policy.check_provider(&entry.provider, &entry.base_url).is_err() {
+                continue;
+            }
+        }
+        if let Ok(sizes) = provider::provider_for(&entry, &key_sources)?.model_sizes().await {
+            installed.insert(entry.id.clone(), sizes);
+        }
     }
 
+    Ok(recommend::recommend_packs(&config, policy.as_ref(), &hardware, &installed))
+}
+
+#[tauri::command]
+pub async fn engine_install_pack(
+    pack_id: String,
+    state: State<'_, ConfigState>,
+) -> Result<EngineHealth, String> {
+    let config = state.get()?;
+    policy::check_pack(&pack_id)?;
+
     // Get the pack
     let pack = config.packs.get(&pack_id)
         .ok_or(format!("Pack '{}' not found", pack_id))?;
 
-    // Install each model
-    let models_to_install = vec![
-        pack.analysis_model.clone(),
-        pack.report_model.clone(),
-        pack.embedding_model.clone(),
-    ];
-
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(300)) // 5 minutes for model downloads
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    for model in models_to_install {
-        // Use Ollama's pull API
-        let pull_url = format!("{}/api/pull", base_url);
-        let pull_body = serde_json::json!({
-            "name": model,
-            "stream": false
-        });
-
-        let response = client
-            .post(&pull_url)
-            .json(&pull_body)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to pull model {}: {}", model, e))?;
+    // Install each model on the provider its role is routed to
+    for (provider_id, models) in pack.models_by_provider() {
+        let entry = config.provider_by_id(&provider_id)?;
+        policy::check_provider(&entry.provider, &entry.base_url)?;
 
-        if !response.status().is_success() {
-            return Err(format!("Failed to pull model {}: {}", model, response.status()));
+        let provider = provider::provider_for(&entry, &KeySources::from_config(&config))?;
+        provider.health().await?;
+        for model in models {
+            provider.install_model(&model).await?;
         }
     }
 
     // Update active pack
-    config.active_pack_id = Some(pack_id);
-    engine_config::save_config(&config)?;
+    let config = state.update(|config| {
+        if !config.packs.contains_key(&pack_id) {
+            return Err(format!("Pack '{}' not found", pack_id));
+        }
+        config.active_pack_id = Some(pack_id);
+        Ok(())
+    })?;
 
     // Return updated health
-    engine_health().await
+    collect_health(&config).await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackSummary {
+    pub id: String,
+    pub pack: ModelPack,
+    pub builtin: bool,
+    // Built-in the user keeps their own copy of instead of following the shipped version
+    pub pinned: bool,
+    // Pinned copy that no longer matches what this release ships
+    pub differs_from_shipped: bool,
+    pub active: bool,
+    // The organization policy lets the user activate this pack
+    pub approved: bool,
+    // The organization policy manages packs, so this one cannot be edited
+    pub locked: bool,
+}
+
+fn pack_summaries(config: &EngineConfig) -> Result<Vec<PackSummary>, String> {
+    let policy = policy::load_policy()?.unwrap_or_default();
+    let builtins = engine_config::builtin_packs();
+    let mut packs: Vec<PackSummary> = config
+        .packs
+        .iter()
+        .map(|(id, pack)| {
+            let shipped = builtins.get(id);
+            PackSummary {
+                id: id.clone(),
+                pack: pack.clone(),
+                builtin: shipped.is_some(),
+                pinned: config.pinned_packs.contains(id),
+                differs_from_shipped: shipped.map(|s| s != pack).unwrap_or(false),
+                active: config.active_pack_id.as_deref() == Some(id.as_str()),
+                approved: policy.is_pack_approved(id),
+                locked: policy.packs_locked(),
+            }
+        })
+        .collect();
+
+    // Built-ins first, then user packs by id
+    packs.sort_by(|a, b| b.builtin.cmp(&a.builtin).then_with(|| a.id.cmp(&b.id)));
+    Ok(packs)
+}
+
+#[tauri::command]
+pub async fn engine_list_packs(state: State<'_, ConfigState>) -> Result<Vec<PackSummary>, String> {
+    let config = state.get()?;
+    pack_summaries(&config)
+}
+
+#[tauri::command]
+pub async fn engine_create_pack(
+    pack_id: String,
+    pack: ModelPack,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<PackSummary>, String> {
+    policy::check_pack_edit()?;
+    engine_config::validate_pack_id(&pack_id)?;
+    engine_config::validate_pack(&pack)?;
+
+    let config = state.update(|config| {
+        if config.packs.contains_key(&pack_id) {
+            return Err(format!("Pack '{}' already exists", pack_id));
+        }
+        config.packs.insert(pack_id, pack);
+        Ok(())
+    })?;
+
+    pack_summaries(&config)
+}
+
+#[tauri::command]
+pub async fn engine_update_pack(
+    pack_id: String,
+    pack: ModelPack,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<PackSummary>, String> {
+    policy::check_pack_edit()?;
+    engine_config::validate_pack(&pack)?;
+
+    let config = state.update(|config| {
+        if engine_config::is_builtin_pack(&pack_id) && !config.pinned_packs.contains(&pack_id) {
+            return Err(format!(
+                "Pack '{}' is built in and follows the shipped version. Pin it to edit your own copy.",
+                pack_id
+            ));
+        }
+
+        let existing = config
+            .packs
+            .get_mut(&pack_id)
+            .ok_or(format!("Pack '{}' not found", pack_id))?;
+        *existing = pack;
+        Ok(())
+    })?;
+
+    pack_summaries(&config)
+}
+
+#[tauri::command]
+pub async fn engine_delete_pack(
+    pack_id: String,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<PackSummary>, String> {
+    policy::check_pack_edit()?;
+    if engine_config::is_builtin_pack(&pack_id) {
+        return Err(format!(
+            "Pack '{}' is built in and cannot be deleted. Unpin it to go back to the shipped version.",
+            pack_id
+        ));
+    }
+
+    let config = state.update(|config| {
+        if config.active_pack_id.as_deref() == Some(pack_id.as_str()) {
+            return Err(format!(
+                "Pack '{}' is the active pack. Switch to another pack before deleting it.",
+                pack_id
+            ));
+        }
+
+        config
+            .packs
+            .remove(&pack_id)
+            .ok_or(format!("Pack '{}' not found", pack_id))?;
+        Ok(())
+    })?;
+
+    pack_summaries(&config)
+}
+
+#[tauri::command]
+pub async fn engine_pin_pack(
+    pack_id: String,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<PackSummary>, String> {
+    policy::check_pack_edit()?;
+    let config = state.update(|config| engine_config::pin_pack(config, &pack_id))?;
+
+    pack_summaries(&config)
+}
+
+#[tauri::command]
+pub async fn engine_unpin_pack(
+    pack_id: String,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<PackSummary>, String> {
+    policy::check_pack_edit()?;
+    let config = state.update(|config| engine_config::unpin_pack(config, &pack_id))?;
+
+    pack_summaries(&config)
+}
+
+// Switch packs without pulling models; engine_install_pack does both
+#[tauri::command]
+pub async fn engine_set_active_pack(
+    pack_id: String,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<PackSummary>, String> {
+    policy::check_pack(&pack_id)?;
+    let config = state.update(|config| {
+        if !config.packs.contains_key(&pack_id) {
+            return Err(format!("Pack '{}' not found", pack_id));
+        }
+        config.active_pack_id = Some(pack_id);
+        Ok(())
+    })?;
+
+    pack_summaries(&config)
+}
+
+// Pack import/export
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackConflict {
+    // Same id and same definition; importing changes nothing
+    Identical,
+    // Same id as one of the user's packs with a different definition
+    Modified,
+    // Same id as a built-in pack with a different definition
+    Builtin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportConflictStrategy {
+    Skip,
+    Replace,
+    // Import under a free id such as `<id>_2`
+    Rename,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackImportItem {
+    pub id: String,
+    pub pack: ModelPack,
+    pub conflict: Option<PackConflict>,
+    // Models this pack needs that are not installed yet
+    pub models_to_pull: Vec<String>,
+    // Providers the pack routes to that are not configured here. Importing drops those routes,
+    // so `pack` and `models_to_pull` already describe it running on the primary provider.
+    pub unresolved_routes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackImportPreview {
+    pub packs: Vec<PackImportItem>,
+    // Every model installing the imported packs would pull
+    pub models_to_pull: Vec<String>,
+    // false when Ollama could not be asked, so every required model is listed
+    pub installed_models_known: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedPack {
+    pub from_id: String,
+    pub id: String,
+    // Routes to providers not configured here, dropped on import
+    pub cleared_routes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackImportResult {
+    pub imported: Vec<ImportedPack>,
+    pub skipped: Vec<String>,
+    pub packs: Vec<PackSummary>,
+}
+
+fn pack_conflict(config: &EngineConfig, pack_id: &str, pack: &ModelPack) -> Option<PackConflict> {
+    let existing = config.packs.get(pack_id)?;
+    if existing == pack {
+        Some(PackConflict::Identical)
+    } else if engine_config::is_builtin_pack(pack_id) {
+        Some(PackConflict::Builtin)
+    } else {
+        Some(PackConflict::Modified)
+    }
 }
 
+#[tauri::command]
+pub async fn engine_export_packs(
+    pack_ids: Vec<String>,
+    path: String,
+    state: State<'_, ConfigState>,
+) -> Result<(), String> {
+    let config = state.get()?;
+    let pack_file = engine_config::export_packs(&config, &pack_ids)?;
+    engine_config::write_pack_file(std::path::Path::new(&path), &pack_file)
+}
+
+// Show what a pack file holds, how it collides with existing packs and what it would pull,
+// without changing anything
+#[tauri::command]
+pub async fn engine_preview_pack_import(
+    path: String,
+    state: State<'_, ConfigState>,
+) -> Result<PackImportPreview, String> {
+    let config = state.get()?;
+    let pack_file = engine_config::read_pack_file(std::path::Path::new(&path))?;
+
+    let mut installed_models_known = true;
+    let mut models_to_pull = Vec::new();
+    let mut packs = Vec::new();
+
+    for (id, mut pack) in pack_file.packs {
+        let unresolved_routes = engine_config::clear_unresolved_routes(&config, &mut pack);
+        let (missing, known) = check_missing_models(&config, &pack).await;
+        installed_models_known &= known;
+        for model in &missing {
+            if !models_to_pull.contains(model) {
+                models_to_pull.push(model.clone());
+            }
+        }
+        packs.push(PackImportItem {
+            conflict: pack_conflict(&config, &id, &pack),
+            id,
+            pack,
+            models_to_pull: missing,
+            unresolved_routes,
+        });
+    }
+
+    Ok(PackImportPreview {
+        packs,
+        models_to_pull,
+        installed_models_known,
+    })
+}
+
+// Import packs from a file; `pack_ids` limits it to some of them. Identical packs are
+// always skipped. Replacing a built-in pins it to the imported copy.
+#[tauri::command]
+pub async fn engine_import_packs(
+    path: String,
+    on_conflict: ImportConflictStrategy,
+    pack_ids: Option<Vec<String>>,
+    state: State<'_, ConfigState>,
+) -> Result<PackImportResult, String> {
+    policy::check_pack_edit()?;
+    let mut pack_file = engine_config::read_pack_file(std::path::Path::new(&path))?;
+
+    if let Some(pack_ids) = &pack_ids {
+        if let Some(missing) = pack_ids.iter().find(|id| !pack_file.packs.contains_key(*id)) {
+            return Err(format!("Pack '{}' is not in the pack file", missing));
+        }
+        pack_file.packs.retain(|id, _| pack_ids.contains(id));
+    }
+
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+
+    let config = state.update(|config| {
+        for (from_id, mut pack) in pack_file.packs {
+            let cleared_routes = engine_config::clear_unresolved_routes(config, &mut pack);
+            let id = match (pack_conflict(config, &from_id, &pack), on_conflict) {
+                (None, _) => from_id.clone(),
+                (Some(PackConflict::Identical), _) | (Some(_), ImportConflictStrategy::Skip) => {
+                    skipped.push(from_id);
+                    continue;
+                }
+                (Some(PackConflict::Builtin), ImportConflictStrategy::Replace) => {
+                    engine_config::pin_pack(config, &from_id)?;
+                    from_id.clone()
+                }
+                (Some(_), ImportConflictStrategy::Replace) => from_id.clone(),
+                (Some(_), ImportConflictStrategy::Rename) => {
+                    engine_config::unused_pack_id(config, &from_id)
+                }
+            };
+
+            config.packs.insert(id.clone(), pack);
+            imported.push(ImportedPack {
+                from_id,
+                id,
+                cleared_routes,
+            });
+        }
+        Ok(())
+    })?;
+
+    Ok(PackImportResult {
+        imported,
+        skipped,
+        packs: pack_summaries(&config)?,
+    })
+}
+
+// Provider fallback
+
+// A provider entry, with `model` set to what to ask it for, paired with its client
+type Candidate = (ProviderConfig, Box<dyn EngineProvider>);
+
+// Every provider in the chain for a role the policy allows, in order, starting with the
+// provider the role is routed to. Entries left out are added to `skipped` with the reason.
+fn provider_candidates(
+    config: &EngineConfig,
+    pack: &ModelPack,
+    role: &RoleModel,
+    skipped: &mut Vec<String>,
+) -> Result<Vec<Candidate>, String> {
+    let key_sources = KeySources::from_config(config);
+    let (chain, unusable) = config.provider_chain_for(pack, role)?;
+    skipped.extend(unusable);
+    let candidates = chain
+        .into_iter()
+        .filter_map(|entry| {
+            let provider = policy::check_provider(&entry.provider, &entry.base_url)
+                .and_then(|_| provider::provider_for(&entry, &key_sources));
+            match provider {
+                Ok(provider) => Some((entry, provider)),
+                Err(e) => {
+                    skipped.push(format!("{}: {}", entry.id, e));
+                    None
+                }
+            }
+        })
+        .collect();
+    Ok(candidates)
+}
+
+fn no_provider_answered(skipped: &[String]) -> String {
+    format!("No engine provider could answer.\n{}", skipped.join("\n"))
+}
+
+fn check_active_pack_policy(config: &EngineConfig) -> Result<(), String> {
+    match &config.active_pack_id {
+        Some(pack_id) => policy::check_pack(pack_id),
+        None => Ok(()),
+    }
+}
+
+// Falls through to the next provider if one fails before streaming anything. Once text
+// has reached the UI a failure is returned as is, since a second answer cannot be spliced in.
 #[tauri::command]
 pub async fn engine_chat(
     request: EngineChatRequest,
     window: Window,
-) -> Result<String, String> {
-    let config = engine_config::load_config()?;
-    let base_url = &config.base_url;
+    state: State<'_, ConfigState>,
+) -> Result<EngineChatResponse, String> {
+    let config = state.get()?;
+    check_active_pack_policy(&config)?;
 
     // Get active pack
     let pack = engine_config::get_active_pack(&config)
         .ok_or("No active engine pack configured".to_string())?;
 
-    // Select model based on role
-    let model = match request.role.as_str() {
-        "analysis" | "gossip" => &pack.analysis_model,
-        _ => &pack.analysis_model,
-    };
+    // Select model, provider and sampling based on role
+    let role = pack.chat_role(&request.role)?;
 
     // Build system prompt
     let system_prompt = build_system_prompt(&request.role, &request.project_meta.audience);
@@ -243,142 +743,306 @@ pub async fn engine_chat(
         &request.project_meta,
     );
 
-    // Call Ollama
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(90))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    let chat_url = format!("{}/v1/chat/completions", base_url);
-    let chat_body = serde_json::json!({
-        "model": model,
-        "messages": [
-            { "role": "system", "content": system_prompt },
-            { "role": "user", "content": user_prompt }
-        ],
-        "stream": true,
-        "temperature": 0.8,
-        "top_p": 0.6,
-        "top_k": 2,
-    });
-
-    let mut response = client
-        .post(&chat_url)
-        .json(&chat_body)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to call Ollama: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Ollama error: {}", error_text));
-    }
-
-    // Stream response
-    let mut full_content = String::new();
-    let mut stream = response.bytes_stream();
+    let mut skipped = Vec::new();
+    let candidates = provider_candidates(&config, pack, &role, &mut skipped)?;
+    let messages = vec![ChatMessage::system(system_prompt), ChatMessage::user(user_prompt)];
+
+    let response = chat_with_fallback(
+        candidates,
+        &request.role,
+        &role,
+        messages,
+        skipped,
+        &mut |provider_id, content| {
+            // Emit chunk event
+            window
+                .emit(
+                    "engine_chat_chunk",
+                    EngineChatChunk {
+                        content: content.to_string(),
+                        provider_id: provider_id.to_string(),
+                    },
+                )
+                .map_err(|e| format!("Failed to emit chunk: {}", e))
+        },
+    )
+    .await?;
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Stream error: {}", e))?;
-        let text = String::from_utf8_lossy(&chunk);
+    // Emit done event
+    window
+        .emit(
+            "engine_chat_done",
+            EngineChatDone {
+                model_name: response.model_name.clone(),
+                provider_id: response.provider_id.clone(),
+                provider: response.provider.clone(),
+            },
+        )
+        .map_err(|e| format!("Failed to emit done: {}", e))?;
 
-        // Parse SSE format
-        for line in text.lines() {
-            if line.starts_with("data: ") {
-                let data = &line[6..];
-                if data == "[DONE]" {
-                    continue;
-                }
+    Ok(response)
+}
 
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(data) {
-                    if let Some(choices) = json.get("choices").and_then(|c| c.as_array()) {
-                        if let Some(choice) = choices.get(0) {
-                            if let Some(delta) = choice.get("delta") {
-                                if let Some(content) = delta.get("content").and_then(|c| c.as_str()) {
-                                    full_content.push_str(content);
-
-                                    // Emit chunk event
-                                    window.emit("engine_chat_chunk", content)
-                                        .map_err(|e| format!("Failed to emit chunk: {}", e))?;
-                                }
-                            }
-                        }
-                    }
-                }
+// Stream from each candidate in turn; `on_chunk` gets the answering provider's id with
+// each piece of text
+async fn chat_with_fallback(
+    candidates: Vec<Candidate>,
+    role_name: &str,
+    role: &RoleModel,
+    messages: Vec<ChatMessage>,
+    mut skipped: Vec<String>,
+    on_chunk: &mut (dyn FnMut(&str, &str) -> Result<(), String> + Send),
+) -> Result<EngineChatResponse, String> {
+    for (entry, provider) in candidates {
+        let model_name = entry.model.clone().unwrap_or_else(|| role.model.clone());
+        let completion = CompletionRequest {
+            model: model_name.clone(),
+            messages: messages.clone(),
+            settings: provider.generation_settings(role_name, &role.settings),
+        };
+
+        let mut streamed = false;
+        let result = provider
+            .chat_stream(completion, &mut |content| {
+                streamed = true;
+                on_chunk(&entry.id, content)
+            })
+            .await;
+
+        match result {
+            Ok(content) => {
+                return Ok(EngineChatResponse {
+                    content,
+                    model_name,
+                    provider_id: entry.id,
+                    provider: entry.provider,
+                });
             }
+            Err(e) if !streamed => skipped.push(format!("{}: {}", entry.id, e)),
+            Err(e) => return Err(e),
         }
     }
 
-    // Emit done event
-    window.emit("engine_chat_done", ())
-        .map_err(|e| format!("Failed to emit done: {}", e))?;
-
-    Ok(full_content)
+    Err(no_provider_answered(&skipped))
 }
 
 #[tauri::command]
 pub async fn engine_generate_report(
     request: EngineReportRequest,
+    state: State<'_, ConfigState>,
 ) -> Result<EngineReportResponse, String> {
-    let config = engine_config::load_config()?;
-    let base_url = &config.base_url;
+    let config = state.get()?;
+    check_active_pack_policy(&config)?;
 
     // Get active pack
     let pack = engine_config::get_active_pack(&config)
         .ok_or("No active engine pack configured".to_string())?;
 
-    let model = &pack.report_model;
+    let role = pack.role("report")?;
 
     // Build prompt
     let prompt = build_report_prompt(&request.template_type, &request.audience, &request.data_summary);
 
-    // Call Ollama
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(120))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    let chat_url = format!("{}/v1/chat/completions", base_url);
-    let chat_body = serde_json::json!({
-        "model": model,
-        "messages": [
-            { "role": "user", "content": prompt }
-        ],
-        "stream": false,
-        "temperature": 0.7,
-        "top_p": 0.8,
+    let mut skipped = Vec::new();
+    for (entry, provider) in provider_candidates(&config, pack, &role, &mut skipped)? {
+        let model_name = entry.model.clone().unwrap_or_else(|| role.model.clone());
+        let completion = CompletionRequest {
+            model: model_name.clone(),
+            messages: vec![ChatMessage::user(prompt.clone())],
+            settings: provider.generation_settings("report", &role.settings),
+        };
+
+        match provider.complete(completion).await {
+            Ok(content) => {
+                return Ok(EngineReportResponse {
+                    markdown: content,
+                    model_name,
+                    provider_id: entry.id,
+                    provider: entry.provider,
+                })
+            }
+            Err(e) => skipped.push(format!("{}: {}", entry.id, e)),
+        }
+    }
+
+    Err(no_provider_answered(&skipped))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyValidationStatus {
+    Valid,
+    Invalid,
+    RateLimited,
+    NetworkUnreachable,
+    // The provider type or base URL is wrong, so no request was made
+    ConfigError,
+    Unexpected,
+    // The provider does not authenticate, so there is nothing to check
+    NotApplicable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyValidationResult {
+    pub status: KeyValidationStatus,
+    pub message: String,
+    pub http_status: Option<u16>,
+    pub stored: bool,
+}
+
+// Check a key through the provider it is for, so the request matches what the provider
+// itself sends (endpoint, auth header, base URL handling)
+async fn check_api_key(provider: &str, base_url: &str, key: &str) -> KeyValidationResult {
+    let result = |status, message: String, http_status| KeyValidationResult {
+        status,
+        message,
+        http_status,
+        stored: false,
+    };
+
+    let entry = ProviderConfig {
+        id: PRIMARY_PROVIDER_ID.to_string(),
+        provider: provider.to_string(),
+        base_url: base_url.to_string(),
+        credential_id: None,
+        model: None,
+    };
+    let checker = engine_config::validate_provider(&entry)
+        .and_then(|_| provider::provider_for(&entry, &KeySources::default()));
+    let checked = match checker {
+        Ok(checker) => checker.check_key(key).await,
+        Err(e) => return result(KeyValidationStatus::ConfigError, e, None),
+    };
+
+    let status = match checked {
+        Ok(Some(status)) => status,
+        // Ollama serves every request without a key, so any key would look valid
+        Ok(None) => {
+            return result(
+                KeyValidationStatus::NotApplicable,
+                format!("The {} provider does not use API keys", provider),
+                None,
+            )
+        }
+        Err(e) => return result(KeyValidationStatus::NetworkUnreachable, e, None),
+    };
+
+    let (validation, message) = match status {
+        200..=299 => (KeyValidationStatus::Valid, "API key is valid".to_string()),
+        401 | 403 => (
+            KeyValidationStatus::Invalid,
+            "Invalid API key. Please check your key and try again.".to_string(),
+        ),
+        429 => (
+            KeyValidationStatus::RateLimited,
+            "Rate limit exceeded. Please try again later.".to_string(),
+        ),
+        _ => (
+            KeyValidationStatus::Unexpected,
+            format!("Provider returned status: {}", status),
+        ),
+    };
+
+    result(validation, message, Some(status))
+}
+
+// Provider, endpoint and credential id a key belongs to, defaulting to the configured provider
+fn resolve_key_target(
+    config: &EngineConfig,
+    credential_id: Option<String>,
+    provider: Option<String>,
+    base_url: Option<String>,
+) -> (String, String, String) {
+    let provider = provider.unwrap_or_else(|| config.provider.clone());
+    let base_url = base_url.unwrap_or_else(|| match provider.as_str() {
+        "anthropic" => ANTHROPIC_BASE_URL.to_string(),
+        _ => config.base_url.clone(),
     });
+    let credential_id = credential_id
+        .or_else(|| config.credential_id.clone())
+        .unwrap_or_else(|| api_key::DEFAULT_CREDENTIAL_ID.to_string());
 
-    let response = client
-        .post(&chat_url)
-        .json(&chat_body)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to call Ollama: {}", e))?;
+    (credential_id, provider, base_url)
+}
 
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Ollama error: {}", error_text));
+#[tauri::command]
+pub async fn validate_api_key(
+    key: Zeroizing<String>,
+    credential_id: Option<String>,
+    provider: Option<String>,
+    base_url: Option<String>,
+    store: bool,
+    state: State<'_, ConfigState>,
+) -> Result<KeyValidationResult, String> {
+    let config = state.get()?;
+    let (credential_id, provider, base_url) =
+        resolve_key_target(&config, credential_id, provider, base_url);
+    policy::check_provider(&provider, &base_url)?;
+
+    let mut result = check_api_key(&provider, &base_url, &key).await;
+
+    if store && result.status == KeyValidationStatus::Valid {
+        api_key::store_api_key(&credential_id, &key)?;
+        api_key::mark_validated(&credential_id)?;
+        result.stored = true;
     }
 
-    let json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-    let content = json
-        .get("choices")
-        .and_then(|c| c.as_array())
-        .and_then(|c| c.get(0))
-        .and_then(|c| c.get("message"))
-        .and_then(|m| m.get("content"))
-        .and_then(|c| c.as_str())
-        .ok_or("Invalid response format")?;
-
-    Ok(EngineReportResponse {
-        markdown: content.to_string(),
-        model_name: model.clone(),
-    })
+    Ok(result)
+}
+
+// Swap in a new key only once it has been shown to work; the old key stays otherwise
+#[tauri::command]
+pub async fn rotate_api_key(
+    new_key: Zeroizing<String>,
+    credential_id: Option<String>,
+    provider: Option<String>,
+    base_url: Option<String>,
+    expires_at: Option<u64>,
+    state: State<'_, ConfigState>,
+) -> Result<KeyValidationResult, String> {
+    let config = state.get()?;
+    let (credential_id, provider, base_url) =
+        resolve_key_target(&config, credential_id, provider, base_url);
+    policy::check_provider(&provider, &base_url)?;
+
+    if !api_key::has_api_key(&credential_id, &KeySources::from_config(&config)) {
+        return Err(format!("No API key stored as '{}' to rotate", credential_id));
+    }
+
+    let mut result = check_api_key(&provider, &base_url, &new_key).await;
+
+    if result.status == KeyValidationStatus::Valid {
+        api_key::rotate_api_key(&credential_id, &new_key, expires_at, &KeySources::from_config(&config))?;
+        result.stored = true;
+        result.message = "API key rotated".to_string();
+    }
+
+    Ok(result)
+}
+
+// Proxy an Anthropic Messages API call so the API key never leaves the Rust side
+// Goes to the configured Anthropic provider's endpoint, or the public API if none is configured
+#[tauri::command]
+pub async fn anthropic_messages(
+    body: serde_json::Value,
+    credential_id: Option<String>,
+    state: State<'_, ConfigState>,
+) -> Result<serde_json::Value, String> {
+    let config = state.get()?;
+    let key_sources = KeySources::from_config(&config);
+    let entry = config.provider_chain().into_iter().find(|p| p.provider == "anthropic");
+    let base_url = entry
+        .as_ref()
+        .map(|p| p.base_url.clone())
+        .unwrap_or_else(|| ANTHROPIC_BASE_URL.to_string());
+    policy::check_provider("anthropic", &base_url)?;
+
+    let credential_id = credential_id
+        .or_else(|| entry.and_then(|p| p.credential_id))
+        .or(config.credential_id)
+        .unwrap_or_else(|| api_key::DEFAULT_CREDENTIAL_ID.to_string());
+
+    AnthropicProvider::new(&base_url, &credential_id, &key_sources).messages(&body).await
 }
 
 // Helper functions for prompt building
@@ -485,3 +1149,193 @@ Constraints:
     )
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_config::GenerationSettings;
+    use crate::mock_server::{self, Response};
+
+    fn keyless_candidate(id: &str, base_url: &str, model: Option<&str>) -> Candidate {
+        let entry = ProviderConfig {
+            id: id.to_string(),
+            provider: "openai_compatible".to_string(),
+            base_url: base_url.to_string(),
+            credential_id: None,
+            model: model.map(str::to_string),
+        };
+        let provider = provider::provider_for(&entry, &KeySources::default()).unwrap();
+        (entry, provider)
+    }
+
+    fn chat_role() -> RoleModel {
+        RoleModel {
+            model: "role-model".to_string(),
+            provider_id: None,
+            settings: GenerationSettings::default(),
+        }
+    }
+
+    // Chat through `candidates`, returning the result and each chunk with its provider's id
+    async fn chat(candidates: Vec<Candidate>) -> (Result<EngineChatResponse, String>, Vec<(String, String)>) {
+        let mut chunks = Vec::new();
+        let result = chat_with_fallback(
+            candidates,
+            "analysis",
+            &chat_role(),
+            vec![ChatMessage::user("Hi")],
+            Vec::new(),
+            &mut |provider_id, content| {
+                chunks.push((provider_id.to_string(), content.to_string()));
+                Ok(())
+            },
+        )
+        .await;
+        (result, chunks)
+    }
+
+    #[tokio::test]
+    async fn chat_falls_back_when_the_first_provider_fails() {
+        let overloaded = Response::json(500, r#"{"error":"overloaded"}"#);
+        let (primary_url, primary) = mock_server::serve(vec![overloaded]);
+        let events = "data: {\"choices\":[{\"delta\":{\"content\":\"Hello\"}}]}\n\ndata: [DONE]\n\n";
+        let (backup_url, backup) = mock_server::serve(vec![Response::sse(events)]);
+
+        let (result, chunks) = chat(vec![
+            keyless_candidate(PRIMARY_PROVIDER_ID, &primary_url, None),
+            keyless_candidate("backup", &backup_url, Some("backup-model")),
+        ])
+        .await;
+        let primary_requests = primary.join().unwrap();
+        let backup_requests = backup.join().unwrap();
+
+        let response = result.unwrap();
+        assert_eq!(response.content, "Hello");
+        assert_eq!(response.provider_id, "backup");
+        assert_eq!(response.model_name, "backup-model");
+        assert_eq!(chunks, vec![("backup".to_string(), "Hello".to_string())]);
+        assert!(primary_requests[0].contains("\"model\":\"role-model\""));
+        assert!(backup_requests[0].contains("\"model\":\"backup-model\""));
+    }
+
+    #[tokio::test]
+    async fn chat_reports_every_provider_when_none_answers() {
+        let overloaded = Response::json(500, r#"{"error":"overloaded"}"#);
+        let (primary_url, primary) = mock_server::serve(vec![overloaded]);
+
+        let (result, chunks) = chat(vec![
+            keyless_candidate(PRIMARY_PROVIDER_ID, &primary_url, None),
+            keyless_candidate("backup", &mock_server::closed_url(), Some("backup-model")),
+        ])
+        .await;
+        primary.join().unwrap();
+
+        let error = result.unwrap_err();
+        assert!(error.starts_with("No engine provider could answer."), "{}", error);
+        assert!(error.contains("primary: ") && error.contains("overloaded"), "{}", error);
+        assert!(error.contains("backup: "), "{}", error);
+        assert!(chunks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn chat_does_not_fall_back_once_text_has_streamed() {
+        let events = concat!(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+            "data: {\"error\":{\"message\":\"connection reset upstream\"}}\n\n",
+        );
+        let (primary_url, primary) = mock_server::serve(vec![Response::sse(events)]);
+        // Answers if asked, which it must not be
+        let backup_events = "data: {\"choices\":[{\"delta\":{\"content\":\"Hello\"}}]}\n\ndata: [DONE]\n\n";
+        let (backup_url, _backup) = mock_server::serve(vec![Response::sse(backup_events)]);
+
+        let (result, chunks) = chat(vec![
+            keyless_candidate(PRIMARY_PROVIDER_ID, &primary_url, None),
+            keyless_candidate("backup", &backup_url, Some("backup-model")),
+        ])
+        .await;
+        primary.join().unwrap();
+
+        let error = result.unwrap_err();
+        assert!(error.contains("connection reset upstream"), "{}", error);
+        assert!(!error.contains("No engine provider could answer"), "{}", error);
+        assert_eq!(chunks, vec![(PRIMARY_PROVIDER_ID.to_string(), "Hel".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn anthropic_key_is_checked_against_the_models_list() {
+        let (base_url, server) = mock_server::serve(vec![Response::json(200, r#"{"data":[]}"#)]);
+
+        let result = check_api_key("anthropic", &base_url, "sk-ant-test").await;
+        let requests = server.join().unwrap();
+
+        assert_eq!(result.status, KeyValidationStatus::Valid);
+        assert_eq!(result.http_status, Some(200));
+        assert!(requests[0].starts_with("GET /v1/models "));
+        assert!(requests[0].contains("x-api-key: sk-ant-test"));
+    }
+
+    #[tokio::test]
+    async fn openai_compatible_key_is_sent_as_bearer() {
+        let (base_url, server) = mock_server::serve(vec![Response::json(200, r#"{"data":[]}"#)]);
+
+        let result = check_api_key("openai_compatible", &base_url, "sk-test").await;
+        let requests = server.join().unwrap();
+
+        assert_eq!(result.status, KeyValidationStatus::Valid);
+        assert!(requests[0].starts_with("GET /v1/models "));
+        assert!(requests[0].contains("authorization: Bearer sk-test"));
+    }
+
+    #[tokio::test]
+    async fn openai_compatible_base_url_may_include_v1() {
+        let (base_url, server) = mock_server::serve(vec![Response::json(200, r#"{"data":[]}"#)]);
+
+        let result = check_api_key("openai_compatible", &format!("{}/v1/", base_url), "sk-test").await;
+        let requests = server.join().unwrap();
+
+        assert_eq!(result.status, KeyValidationStatus::Valid);
+        assert!(requests[0].starts_with("GET /v1/models "));
+    }
+
+    #[tokio::test]
+    async fn config_errors_are_not_reported_as_network_errors() {
+        let result = check_api_key("nonexistent", &mock_server::closed_url(), "sk-test").await;
+        assert_eq!(result.status, KeyValidationStatus::ConfigError);
+
+        let result = check_api_key("anthropic", "api.anthropic.com", "sk-ant-test").await;
+        assert_eq!(result.status, KeyValidationStatus::ConfigError);
+    }
+
+    #[tokio::test]
+    async fn error_statuses_map_to_validation_statuses() {
+        let cases = [
+            (401, KeyValidationStatus::Invalid),
+            (403, KeyValidationStatus::Invalid),
+            (429, KeyValidationStatus::RateLimited),
+            (500, KeyValidationStatus::Unexpected),
+        ];
+        for (status, expected) in cases {
+            let (base_url, server) = mock_server::serve(vec![Response::json(status, "{}")]);
+            let result = check_api_key("anthropic", &base_url, "sk-ant-test").await;
+            server.join().unwrap();
+
+            assert_eq!(result.status, expected, "HTTP {}", status);
+            assert_eq!(result.http_status, Some(status));
+        }
+    }
+
+    #[tokio::test]
+    async fn unreachable_provider_is_reported_as_such() {
+        let result = check_api_key("anthropic", &mock_server::closed_url(), "sk-ant-test").await;
+
+        assert_eq!(result.status, KeyValidationStatus::NetworkUnreachable);
+        assert_eq!(result.http_status, None);
+    }
+
+    #[tokio::test]
+    async fn ollama_keys_are_not_applicable() {
+        // Nothing listens here; the check must not make a request at all
+        let result = check_api_key("ollama", &mock_server::closed_url(), "anything").await;
+
+        assert_eq!(result.status, KeyValidationStatus::NotApplicable);
+    }
+}
@@ -2,69 +2,434 @@
 // Handles reading/writing engine-config.json and model pack definitions
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
-use std::path::PathBuf;
-use tauri::api::path::app_data_dir;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::app_paths;
+use crate::policy;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModelPack {
     pub label: String,
     pub analysis_model: String,
     pub report_model: String,
     pub embedding_model: String,
+    // Model for the playful "gossip" chat role; the analysis model when unset
+    #[serde(default)]
+    pub gossip_model: Option<String>,
+    // Unset fields fall back to the role defaults below
+    #[serde(default)]
+    pub analysis_settings: GenerationSettings,
+    #[serde(default)]
+    pub gossip_settings: GenerationSettings,
+    #[serde(default)]
+    pub report_settings: GenerationSettings,
+    // Provider id (see EngineConfig::provider_chain) each role runs on; unset means the
+    // primary provider. Gossip follows analysis unless set.
+    #[serde(default)]
+    pub analysis_provider: Option<String>,
+    #[serde(default)]
+    pub gossip_provider: Option<String>,
+    #[serde(default)]
+    pub report_provider: Option<String>,
+    #[serde(default)]
+    pub embedding_provider: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Where one role of a pack runs and with what
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoleModel {
+    pub model: String,
+    pub provider_id: Option<String>,
+    pub settings: GenerationSettings,
+}
+
+pub const PACK_ROLES: [&str; 4] = ["analysis", "gossip", "report", "embedding"];
+
+impl ModelPack {
+    // Model, provider and the pack's own sampling settings for any role. Only what the pack
+    // sets is here; each provider fills in its own defaults (EngineProvider::generation_settings).
+    pub fn role(&self, role: &str) -> Result<RoleModel, String> {
+        let (model, provider_id, settings) = match role {
+            "analysis" => (
+                &self.analysis_model,
+                &self.analysis_provider,
+                self.analysis_settings.clone(),
+            ),
+            "gossip" => (
+                self.gossip_model.as_ref().unwrap_or(&self.analysis_model),
+                if self.gossip_provider.is_some() {
+                    &self.gossip_provider
+                } else {
+                    &self.analysis_provider
+                },
+                self.gossip_settings.clone(),
+            ),
+            "report" => (
+                &self.report_model,
+                &self.report_provider,
+                self.report_settings.clone(),
+            ),
+            "embedding" => (
+                &self.embedding_model,
+                &self.embedding_provider,
+                GenerationSettings::default(),
+            ),
+            other => {
+                return Err(format!(
+                    "Unknown role '{}': expected one of {}",
+                    other,
+                    PACK_ROLES.join(", ")
+                ))
+            }
+        };
+
+        Ok(RoleModel {
+            model: model.clone(),
+            provider_id: provider_id.clone(),
+            settings,
+        })
+    }
+
+    // The first chat or report model the pack runs on the primary provider
+    pub fn primary_chat_model(&self) -> Option<String> {
+        ["analysis", "gossip", "report"]
+            .into_iter()
+            .filter_map(|role| self.role(role).ok())
+            .find(|role| role.provider_id.as_deref().unwrap_or(PRIMARY_PROVIDER_ID) == PRIMARY_PROVIDER_ID)
+            .map(|role| role.model)
+    }
+
+    pub fn chat_role(&self, role: &str) -> Result<RoleModel, String> {
+        match role {
+            "analysis" | "gossip" => self.role(role),
+            other => Err(format!(
+                "Unknown chat role '{}': expected \"analysis\" or \"gossip\"",
+                other
+            )),
+        }
+    }
+
+    // Every distinct model the pack needs, grouped by the provider id that serves it
+    pub fn models_by_provider(&self) -> BTreeMap<String, Vec<String>> {
+        let mut grouped: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for role in PACK_ROLES {
+            if let Ok(role) = self.role(role) {
+                let provider_id = role
+                    .provider_id
+                    .unwrap_or_else(|| PRIMARY_PROVIDER_ID.to_string());
+                let models = grouped.entry(provider_id).or_default();
+                if !models.contains(&role.model) {
+                    models.push(role.model);
+                }
+            }
+        }
+        grouped
+    }
+}
+
+// Sampling and context settings for one model role
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationSettings {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub top_k: Option<u32>,
+    pub max_tokens: Option<u32>,
+    pub num_ctx: Option<u32>,
+    #[serde(default)]
+    pub stop: Vec<String>,
+    pub seed: Option<i64>,
+}
+
+impl GenerationSettings {
+    pub fn analysis_defaults() -> Self {
+        Self {
+            temperature: Some(0.8),
+            top_p: Some(0.9),
+            top_k: Some(40),
+            num_ctx: Some(8192),
+            ..Self::default()
+        }
+    }
+
+    // A little warmer than analysis for the playful phrasing
+    pub fn gossip_defaults() -> Self {
+        Self {
+            temperature: Some(0.9),
+            top_p: Some(0.9),
+            top_k: Some(40),
+            num_ctx: Some(8192),
+            ..Self::default()
+        }
+    }
+
+    pub fn report_defaults() -> Self {
+        Self {
+            temperature: Some(0.7),
+            top_p: Some(0.8),
+            top_k: Some(40),
+            num_ctx: Some(8192),
+            ..Self::default()
+        }
+    }
+
+    // Defaults tuned for local models served by Ollama and llama.cpp-style servers
+    pub fn role_defaults(role: &str) -> Self {
+        match role {
+            "analysis" => Self::analysis_defaults(),
+            "gossip" => Self::gossip_defaults(),
+            "report" => Self::report_defaults(),
+            _ => Self::default(),
+        }
+    }
+
+    // Fill every unset field from `defaults`
+    pub fn or_defaults(&self, defaults: &Self) -> Self {
+        Self {
+            temperature: self.temperature.or(defaults.temperature),
+            top_p: self.top_p.or(defaults.top_p),
+            top_k: self.top_k.or(defaults.top_k),
+            max_tokens: self.max_tokens.or(defaults.max_tokens),
+            num_ctx: self.num_ctx.or(defaults.num_ctx),
+            stop: if self.stop.is_empty() {
+                defaults.stop.clone()
+            } else {
+                self.stop.clone()
+            },
+            seed: self.seed.or(defaults.seed),
+        }
+    }
+
+    pub fn validate(&self, role: &str) -> Result<(), String> {
+        if let Some(t) = self.temperature {
+            if !(0.0..=2.0).contains(&t) {
+                return Err(format!("{} temperature must be between 0 and 2", role));
+            }
+        }
+        if let Some(p) = self.top_p {
+            if !(0.0..=1.0).contains(&p) || p == 0.0 {
+                return Err(format!("{} top_p must be greater than 0 and at most 1", role));
+            }
+        }
+        if self.top_k == Some(0) {
+            return Err(format!("{} top_k must be at least 1", role));
+        }
+        if self.max_tokens == Some(0) {
+            return Err(format!("{} max_tokens must be at least 1", role));
+        }
+        if let Some(n) = self.num_ctx {
+            if n < 512 {
+                return Err(format!("{} num_ctx must be at least 512", role));
+            }
+        }
+        if self.stop.iter().any(|s| s.is_empty()) {
+            return Err(format!("{} stop sequences must not be empty", role));
+        }
+        Ok(())
+    }
+}
+
+// Bump when the on-disk format changes and add a step to MIGRATIONS
+pub const CONFIG_SCHEMA_VERSION: u32 = 4;
+
+// Id of the provider described by EngineConfig's own provider fields
+pub const PRIMARY_PROVIDER_ID: &str = "primary";
+
+// Every provider type provider::provider_for can build
+pub const PROVIDER_TYPES: [&str; 3] = ["ollama", "anthropic", "openai_compatible"];
+
+// One backend the engine can send requests to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    pub id: String,
+    pub provider: String,
+    pub base_url: String,
+    #[serde(default)]
+    pub credential_id: Option<String>,
+    // Model to use here in place of the pack's; unset sends the pack's model name as is,
+    // unless that name belongs to another kind of provider (see provider_chain_for)
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EngineConfig {
+    pub schema_version: u32,
     pub provider: String,
     pub base_url: String,
+    // Id of the stored API key this provider authenticates with, if it needs one
+    pub credential_id: Option<String>,
+    // Managed deployments can provision that key instead of storing it in the OS:
+    // an environment variable name, or a path such as a mounted secret file
+    pub api_key_env: Option<String>,
+    pub api_key_file: Option<String>,
     pub active_pack_id: Option<String>,
-    pub packs: std::collections::HashMap<String, ModelPack>,
+    // In memory this is every pack: shipped built-ins merged with the user's own.
+    // On disk it only holds user-defined packs and pinned copies of built-ins.
+    pub packs: HashMap<String, ModelPack>,
+    // Built-in pack ids the user pinned to their own copy instead of following the shipped version
+    pub pinned_packs: BTreeSet<String>,
+    // Tried in order when the primary provider cannot answer
+    pub fallback_providers: Vec<ProviderConfig>,
 }
 
-impl Default for EngineConfig {
-    fn default() -> Self {
-        let mut packs = std::collections::HashMap::new();
-
-        // Light Fast pack
-        packs.insert(
-            "light_fast".to_string(),
-            ModelPack {
-                label: "Fast & Light".to_string(),
-                analysis_model: "qwen3:4b".to_string(),
-                report_model: "qwen3:4b".to_string(),
-                embedding_model: "qwen3-embedding:4b".to_string(),
-            },
-        );
+impl EngineConfig {
+    pub fn primary_provider(&self) -> ProviderConfig {
+        ProviderConfig {
+            id: PRIMARY_PROVIDER_ID.to_string(),
+            provider: self.provider.clone(),
+            base_url: self.base_url.clone(),
+            credential_id: self.credential_id.clone(),
+            model: None,
+        }
+    }
 
-        // Analyst Fast pack (recommended)
-        packs.insert(
-            "analyst_fast".to_string(),
-            ModelPack {
-                label: "Analyst Pack (Recommended)".to_string(),
-                analysis_model: "gurubot/glm-4.6v-flash-gguf:q4_k_m".to_string(),
-                report_model: "gurubot/glm-4.6v-flash-gguf:q4_k_m".to_string(),
-                embedding_model: "qwen3-embedding:4b".to_string(),
-            },
-        );
+    // The primary provider, then each fallback in order
+    pub fn provider_chain(&self) -> Vec<ProviderConfig> {
+        let mut chain = vec![self.primary_provider()];
+        chain.extend(self.fallback_providers.iter().cloned());
+        chain
+    }
+
+    pub fn provider_by_id(&self, provider_id: &str) -> Result<ProviderConfig, String> {
+        self.provider_chain()
+            .into_iter()
+            .find(|p| p.id == provider_id)
+            .ok_or(format!("Provider '{}' is not configured", provider_id))
+    }
+
+    // Chain for one role of `pack`: the provider it is routed to first, running the pack's
+    // model, then the rest of the chain as fallbacks. Each entry's `model` is set to what it
+    // will be asked for. A routed role's model name belongs to the provider it is routed to,
+    // so a fallback only reuses it when it runs the same kind of provider; otherwise it needs
+    // a model of its own, and the primary runs the pack's model for the primary. Entries with
+    // nothing to run come back in the second list with the reason.
+    pub fn provider_chain_for(
+        &self,
+        pack: &ModelPack,
+        role: &RoleModel,
+    ) -> Result<(Vec<ProviderConfig>, Vec<String>), String> {
+        let mut chain = self.provider_chain();
+        let mut routed_provider = None;
+        if let Some(route) = &role.provider_id {
+            let index = chain
+                .iter()
+                .position(|p| &p.id == route)
+                .ok_or(format!("Provider '{}' is not configured", route))?;
+            let mut routed = chain.remove(index);
+            routed.model = Some(role.model.clone());
+            routed_provider = Some(routed.provider.clone());
+            chain.insert(0, routed);
+        }
+
+        let mut usable = Vec::new();
+        let mut unusable = Vec::new();
+        for mut entry in chain {
+            let model = match (&entry.model, &routed_provider) {
+                (Some(model), _) => Some(model.clone()),
+                (None, None) => Some(role.model.clone()),
+                (None, Some(provider)) if provider == &entry.provider => Some(role.model.clone()),
+                (None, Some(_)) if entry.id == PRIMARY_PROVIDER_ID => pack.primary_chat_model(),
+                (None, Some(_)) => None,
+            };
+            match model {
+                Some(model) => {
+                    entry.model = Some(model);
+                    usable.push(entry);
+                }
+                None => unusable.push(format!(
+                    "{}: no model to stand in for {}; set a model on this provider to use it as a fallback",
+                    entry.id, role.model
+                )),
+            }
+        }
+        Ok((usable, unusable))
+    }
+}
+
+// Packs shipped with the app. Unless pinned, these are read from here on every load,
+// so improving a definition reaches existing users with the next release.
+pub fn builtin_packs() -> HashMap<String, ModelPack> {
+    let mut packs = HashMap::new();
+
+    // Light Fast pack
+    packs.insert(
+        "light_fast".to_string(),
+        ModelPack {
+            label: "Fast & Light".to_string(),
+            analysis_model: "qwen3:4b".to_string(),
+            report_model: "qwen3:4b".to_string(),
+            embedding_model: "qwen3-embedding:4b".to_string(),
+            gossip_model: None,
+            analysis_settings: GenerationSettings::default(),
+            gossip_settings: GenerationSettings::default(),
+            report_settings: GenerationSettings::default(),
+            analysis_provider: None,
+            gossip_provider: None,
+            report_provider: None,
+            embedding_provider: None,
+        },
+    );
+
+    // Analyst Fast pack (recommended)
+    packs.insert(
+        "analyst_fast".to_string(),
+        ModelPack {
+            label: "Analyst Pack (Recommended)".to_string(),
+            analysis_model: "gurubot/glm-4.6v-flash-gguf:q4_k_m".to_string(),
+            report_model: "gurubot/glm-4.6v-flash-gguf:q4_k_m".to_string(),
+            embedding_model: "qwen3-embedding:4b".to_string(),
+            gossip_model: None,
+            analysis_settings: GenerationSettings::default(),
+            gossip_settings: GenerationSettings::default(),
+            report_settings: GenerationSettings::default(),
+            analysis_provider: None,
+            gossip_provider: None,
+            report_provider: None,
+            embedding_provider: None,
+        },
+    );
+
+    packs
+}
+
+// Approximate download sizes in MB of the models the built-in packs use, so a pack can be
+// recommended before anything is installed. Keep in step with builtin_packs.
+const BUILTIN_MODEL_SIZES_MB: [(&str, u64); 3] = [
+    ("qwen3:4b", 2_500),
+    ("qwen3-embedding:4b", 2_500),
+    // 9B GLM-4.6V-Flash; the name carries no parameter count to estimate from
+    ("gurubot/glm-4.6v-flash-gguf:q4_k_m", 6_400),
+];
 
+pub fn builtin_model_bytes(model: &str) -> Option<u64> {
+    BUILTIN_MODEL_SIZES_MB
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(model))
+        .map(|(_, mb)| mb * 1024 * 1024)
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
         Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
             provider: "ollama".to_string(),
             base_url: "http://127.0.0.1:11434".to_string(),
+            credential_id: None,
+            api_key_env: None,
+            api_key_file: None,
             active_pack_id: Some("analyst_fast".to_string()),
-            packs,
+            packs: builtin_packs(),
+            pinned_packs: BTreeSet::new(),
+            fallback_providers: Vec::new(),
         }
     }
 }
 
 pub fn get_config_path() -> Result<PathBuf, String> {
-    // Use a simpler approach - store in app data directory
-    // We'll get the path from Tauri's app data dir helper
-    let app_data = std::env::var("APPDATA")
-        .or_else(|_| std::env::var("LOCALAPPDATA"))
-        .map_err(|_| "Failed to get app data directory".to_string())?;
-
-    let engine_dir = PathBuf::from(app_data).join("DataConfessional").join("engine");
+    let engine_dir = app_paths::config_dir()?.join("engine");
 
     // Create engine directory if it doesn't exist
     if !engine_dir.exists() {
@@ -75,39 +440,790 @@ pub fn get_config_path() -> Result<PathBuf, String> {
     Ok(engine_dir.join("engine-config.json"))
 }
 
+// Set when load_config had to fall back to the last known-good copy this session
+static CONFIG_RECOVERY: Mutex<Option<String>> = Mutex::new(None);
+
+fn backup_path(config_path: &Path) -> PathBuf {
+    config_path.with_extension("json.bak")
+}
+
+fn read_config_file(path: &Path) -> Result<(EngineConfig, u32, String), String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config file: {}", e))?;
+
+    let raw: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    let (raw, from_version) = migrate_config(raw)?;
+
+    let mut config: EngineConfig = serde_json::from_value(raw)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    merge_builtin_packs(&mut config);
+
+    Ok((config, from_version, content))
+}
+
+// What startup and hot reload both accept from disk, so a file never loads on one path
+// and is rejected on the other
+fn read_valid_config_file(path: &Path) -> Result<(EngineConfig, u32, String), String> {
+    let loaded = read_config_file(path)?;
+    validate_config(&loaded.0)?;
+    Ok(loaded)
+}
+
+// Read the file as it is on disk without any of load_config's recovery or rewriting,
+// for picking up edits made outside the app
+pub fn read_config(path: &Path) -> Result<EngineConfig, String> {
+    let (mut config, _, _) = read_valid_config_file(path)?;
+    enforce_policy(&mut config)?;
+    Ok(config)
+}
+
+// The organization policy wins over whatever the file says
+fn apply_policy(config: &mut EngineConfig) -> Result<(), String> {
+    if let Some(policy) = policy::load_policy()? {
+        policy.apply(config);
+    }
+    Ok(())
+}
+
+// Apply the policy, then refuse what it could not bring in line
+fn enforce_policy(config: &mut EngineConfig) -> Result<(), String> {
+    apply_policy(config)?;
+    policy::check_config(config)
+}
+
 pub fn load_config() -> Result<EngineConfig, String> {
-    let config_path = get_config_path()?;
+    load_config_from(&get_config_path()?)
+}
 
-    if !config_path.exists() {
+fn load_config_from(config_path: &Path) -> Result<EngineConfig, String> {
+    if !config_path.exists() && !backup_path(config_path).exists() {
         // Return default config if file doesn't exist
-        let default_config = EngineConfig::default();
-        save_config(&default_config)?;
+        let mut default_config = EngineConfig::default();
+        write_config_to(config_path, &default_config)?;
+        apply_policy(&mut default_config)?;
         return Ok(default_config);
     }
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config file: {}", e))?;
+    let (mut config, from_version, content) = match read_valid_config_file(config_path) {
+        Ok(loaded) => loaded,
+        Err(primary_err) => {
+            let loaded = read_valid_config_file(&backup_path(config_path))
+                .map_err(|_| primary_err.clone())?;
 
-    let config: EngineConfig = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
+            // Set the broken file aside for inspection, then restore the known-good copy
+            let corrupt_path = config_path.with_extension("json.corrupt");
+            let _ = fs::rename(config_path, &corrupt_path);
+            write_config_to(config_path, &loaded.0)?;
+
+            let notice = format!(
+                "engine-config.json could not be loaded ({}). Restored the last known-good copy; the damaged file was kept as {}.",
+                primary_err,
+                corrupt_path.display()
+            );
+            eprintln!("{}", notice);
+            if let Ok(mut recovery) = CONFIG_RECOVERY.lock() {
+                *recovery = Some(notice);
+            }
+
+            loaded
+        }
+    };
+
+    if from_version < CONFIG_SCHEMA_VERSION {
+        // Keep the pre-migration file so a bad migration can be undone by hand
+        let backup_path = config_path.with_file_name(format!("engine-config.v{}.json.bak", from_version));
+        fs::write(&backup_path, &content)
+            .map_err(|e| format!("Failed to back up config before migration: {}", e))?;
+        write_config_to(config_path, &config)?;
+    }
 
+    enforce_policy(&mut config)?;
     Ok(config)
 }
 
+// Schema migrations
+// MIGRATIONS[n] upgrades a version n + 1 document to version n + 2, working on raw JSON so
+// a step never depends on the current shape of EngineConfig
+
+type Migration = fn(&mut serde_json::Map<String, serde_json::Value>);
+
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4];
+
+// Files written before schema_version existed are version 1
+fn config_schema_version(raw: &serde_json::Value) -> Result<u32, String> {
+    match raw.get("schema_version") {
+        None => Ok(1),
+        Some(v) => v
+            .as_u64()
+            .map(|v| v as u32)
+            .ok_or("Invalid schema_version in config".to_string()),
+    }
+}
+
+// Returns the upgraded document and the version it started at
+fn migrate_config(mut raw: serde_json::Value) -> Result<(serde_json::Value, u32), String> {
+    let from_version = config_schema_version(&raw)?;
+
+    if from_version == 0 || from_version > CONFIG_SCHEMA_VERSION {
+        return Err(format!(
+            "Config schema version {} is not supported by this version of the app (expected {} or older)",
+            from_version, CONFIG_SCHEMA_VERSION
+        ));
+    }
+
+    let object = raw
+        .as_object_mut()
+        .ok_or("Invalid config: expected a JSON object".to_string())?;
+
+    for version in from_version..CONFIG_SCHEMA_VERSION {
+        MIGRATIONS[(version - 1) as usize](object);
+        object.insert("schema_version".to_string(), (version + 1).into());
+    }
+
+    Ok((raw, from_version))
+}
+
+// v2 adds the API key reference and managed key injection settings
+fn migrate_v1_to_v2(config: &mut serde_json::Map<String, serde_json::Value>) {
+    for field in ["credential_id", "api_key_env", "api_key_file"] {
+        config.entry(field).or_insert(serde_json::Value::Null);
+    }
+}
+
+// v3 stops storing built-in packs. Copies identical to what v1/v2 shipped are dropped so
+// they follow the shipped version; copies the user edited by hand are kept and pinned.
+fn migrate_v2_to_v3(config: &mut serde_json::Map<String, serde_json::Value>) {
+    let shipped_v2 = serde_json::json!({
+        "light_fast": {
+            "label": "Fast & Light",
+            "analysis_model": "qwen3:4b",
+            "report_model": "qwen3:4b",
+            "embedding_model": "qwen3-embedding:4b"
+        },
+        "analyst_fast": {
+            "label": "Analyst Pack (Recommended)",
+            "analysis_model": "gurubot/glm-4.6v-flash-gguf:q4_k_m",
+            "report_model": "gurubot/glm-4.6v-flash-gguf:q4_k_m",
+            "embedding_model": "qwen3-embedding:4b"
+        }
+    });
+
+    let mut pinned = Vec::new();
+    if let Some(packs) = config.get_mut("packs").and_then(|p| p.as_object_mut()) {
+        for (id, shipped) in shipped_v2.as_object().into_iter().flatten() {
+            match packs.get(id) {
+                Some(stored) if stored == shipped => {
+                    packs.remove(id);
+                }
+                Some(_) => pinned.push(serde_json::Value::String(id.clone())),
+                None => {}
+            }
+        }
+    }
+
+    config.insert("pinned_packs".to_string(), serde_json::Value::Array(pinned));
+}
+
+// v4 adds the provider fallback chain
+fn migrate_v3_to_v4(config: &mut serde_json::Map<String, serde_json::Value>) {
+    config
+        .entry("fallback_providers")
+        .or_insert(serde_json::Value::Array(Vec::new()));
+}
+
+pub fn config_recovery_notice() -> Option<String> {
+    CONFIG_RECOVERY.lock().ok().and_then(|r| r.clone())
+}
+
+// Refuses a config the organization policy does not allow
 pub fn save_config(config: &EngineConfig) -> Result<(), String> {
-    let config_path = get_config_path()?;
+    policy::check_config(config)?;
+    write_config(config)
+}
+
+// Write to a temp file, fsync and rename over the original, so a crash leaves either the
+// old or the new file but never a truncated one. The file being replaced becomes the
+// last known-good backup if it still loads.
+// load_config's own rewrites come straight here so a file that predates the policy can
+// still be migrated or restored.
+fn write_config(config: &EngineConfig) -> Result<(), String> {
+    write_config_to(&get_config_path()?, config)
+}
 
-    let content = serde_json::to_string_pretty(config)
+fn write_config_to(config_path: &Path, config: &EngineConfig) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(&config_for_disk(config))
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
 
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config file: {}", e))?;
+    if read_valid_config_file(config_path).is_ok() {
+        fs::copy(config_path, backup_path(config_path))
+            .map_err(|e| format!("Failed to back up config file: {}", e))?;
+    }
+
+    app_paths::write_atomic(config_path, content.as_bytes(), false)
+        .map_err(|e| format!("Failed to write config file: {}", e))
+}
+
+// Built-in packs
+
+pub fn is_builtin_pack(pack_id: &str) -> bool {
+    builtin_packs().contains_key(pack_id)
+}
+
+// Shipped definitions win for every built-in the user has not pinned
+fn merge_builtin_packs(config: &mut EngineConfig) {
+    for (id, pack) in builtin_packs() {
+        if !config.pinned_packs.contains(&id) {
+            config.packs.insert(id, pack);
+        }
+    }
+    config.pinned_packs.retain(|id| config.packs.contains_key(id));
+}
+
+// Drop unpinned built-ins so the file never freezes a shipped definition
+fn config_for_disk(config: &EngineConfig) -> EngineConfig {
+    let builtins = builtin_packs();
+    let mut stored = config.clone();
+    stored
+        .packs
+        .retain(|id, _| !builtins.contains_key(id) || config.pinned_packs.contains(id));
+    stored
+}
+
+// Keep the current shipped definition as the user's own copy
+pub fn pin_pack(config: &mut EngineConfig, pack_id: &str) -> Result<(), String> {
+    if !is_builtin_pack(pack_id) {
+        return Err(format!("Pack '{}' is not a built-in pack", pack_id));
+    }
+    config.pinned_packs.insert(pack_id.to_string());
+    Ok(())
+}
+
+// Discard the user's copy and follow the shipped definition again
+pub fn unpin_pack(config: &mut EngineConfig, pack_id: &str) -> Result<(), String> {
+    let shipped = builtin_packs()
+        .remove(pack_id)
+        .ok_or(format!("Pack '{}' is not a built-in pack", pack_id))?;
+    config.pinned_packs.remove(pack_id);
+    config.packs.insert(pack_id.to_string(), shipped);
+    Ok(())
+}
+
+pub fn validate_pack_id(pack_id: &str) -> Result<(), String> {
+    let valid = !pack_id.is_empty()
+        && pack_id.len() <= 64
+        && pack_id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid pack id '{}': use 1-64 lowercase letters, digits, '_' or '-'",
+            pack_id
+        ))
+    }
+}
+
+pub fn validate_pack(pack: &ModelPack) -> Result<(), String> {
+    if pack.label.trim().is_empty() {
+        return Err("Pack label must not be empty".to_string());
+    }
+
+    let mut models = vec![
+        ("analysis_model", &pack.analysis_model),
+        ("report_model", &pack.report_model),
+        ("embedding_model", &pack.embedding_model),
+    ];
+    if let Some(gossip_model) = &pack.gossip_model {
+        models.push(("gossip_model", gossip_model));
+    }
+    for (field, model) in models {
+        if model.trim().is_empty() {
+            return Err(format!("Pack {} must not be empty", field));
+        }
+        if model.chars().any(char::is_whitespace) {
+            return Err(format!("Pack {} '{}' must not contain whitespace", field, model));
+        }
+    }
+
+    pack.analysis_settings.validate("Analysis")?;
+    pack.gossip_settings.validate("Gossip")?;
+    pack.report_settings.validate("Report")?;
+
+    Ok(())
+}
+
+// Shareable pack files
+// A standalone JSON file holding one or more packs in the same shape engine-config.json
+// stores them, so a curated pack can be handed to someone else and imported
+
+pub const PACK_FILE_FORMAT: &str = "dataconfessional-model-packs";
+pub const PACK_FILE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackFile {
+    pub format: String,
+    pub version: u32,
+    pub packs: BTreeMap<String, ModelPack>,
+}
+
+pub fn export_packs(config: &EngineConfig, pack_ids: &[String]) -> Result<PackFile, String> {
+    if pack_ids.is_empty() {
+        return Err("Choose at least one pack to export".to_string());
+    }
+
+    let mut packs = BTreeMap::new();
+    for pack_id in pack_ids {
+        let pack = config
+            .packs
+            .get(pack_id)
+            .ok_or(format!("Pack '{}' not found", pack_id))?;
+        packs.insert(pack_id.clone(), pack.clone());
+    }
+
+    Ok(PackFile {
+        format: PACK_FILE_FORMAT.to_string(),
+        version: PACK_FILE_VERSION,
+        packs,
+    })
+}
+
+pub fn write_pack_file(path: &Path, pack_file: &PackFile) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(pack_file)
+        .map_err(|e| format!("Failed to serialize packs: {}", e))?;
+    app_paths::write_atomic(path, content.as_bytes(), false)
+        .map_err(|e| format!("Failed to write pack file: {}", e))
+}
+
+// Every pack is validated like engine_create_pack would, so a bad file is refused whole
+pub fn read_pack_file(path: &Path) -> Result<PackFile, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read pack file: {}", e))?;
+    let pack_file: PackFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse pack file: {}", e))?;
+
+    if pack_file.format != PACK_FILE_FORMAT {
+        return Err(format!(
+            "Not a model pack file (format '{}', expected '{}')",
+            pack_file.format, PACK_FILE_FORMAT
+        ));
+    }
+    if pack_file.version == 0 || pack_file.version > PACK_FILE_VERSION {
+        return Err(format!(
+            "Pack file version {} is not supported by this version of the app (expected {} or older)",
+            pack_file.version, PACK_FILE_VERSION
+        ));
+    }
+    if pack_file.packs.is_empty() {
+        return Err("Pack file does not contain any packs".to_string());
+    }
+
+    for (pack_id, pack) in &pack_file.packs {
+        validate_pack_id(pack_id)?;
+        validate_pack(pack).map_err(|e| format!("Pack '{}': {}", pack_id, e))?;
+    }
+
+    Ok(pack_file)
+}
+
+// First free id of the form `<pack_id>_2`, `<pack_id>_3`, ... that still passes validate_pack_id
+pub fn unused_pack_id(config: &EngineConfig, pack_id: &str) -> String {
+    (2..)
+        .map(|n| {
+            let suffix = format!("_{}", n);
+            let stem: String = pack_id.chars().take(64 - suffix.len()).collect();
+            format!("{}{}", stem, suffix)
+        })
+        .find(|id| !config.packs.contains_key(id))
+        .unwrap_or_else(|| pack_id.to_string())
+}
+
+// Checks a whole config the way the pack commands check each edit
+pub fn validate_config(config: &EngineConfig) -> Result<(), String> {
+    validate_provider_type(&config.provider)?;
+    validate_base_url(&config.base_url)?;
+
+    let mut provider_ids = BTreeSet::from([PRIMARY_PROVIDER_ID.to_string()]);
+    for fallback in &config.fallback_providers {
+        validate_provider(fallback)?;
+        if !provider_ids.insert(fallback.id.clone()) {
+            return Err(format!("Provider id '{}' is used more than once", fallback.id));
+        }
+    }
+
+    for (pack_id, pack) in &config.packs {
+        validate_pack_id(pack_id)?;
+        validate_pack(pack).map_err(|e| format!("Pack '{}': {}", pack_id, e))?;
+        validate_pack_routes(&provider_ids, pack)
+            .map_err(|e| format!("Pack '{}': {}", pack_id, e))?;
+    }
+
+    if let Some(pack_id) = &config.active_pack_id {
+        if !config.packs.contains_key(pack_id) {
+            return Err(format!("Active pack '{}' does not exist", pack_id));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_base_url(base_url: &str) -> Result<(), String> {
+    if base_url.starts_with("http://") || base_url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid base_url '{}': expected an http:// or https:// URL",
+            base_url
+        ))
+    }
+}
+
+fn validate_provider_type(provider: &str) -> Result<(), String> {
+    if PROVIDER_TYPES.contains(&provider) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown provider type '{}': expected one of {}",
+            provider,
+            PROVIDER_TYPES.join(", ")
+        ))
+    }
+}
+
+pub fn validate_provider(provider: &ProviderConfig) -> Result<(), String> {
+    validate_pack_id(&provider.id).map_err(|_| {
+        format!(
+            "Invalid provider id '{}': use 1-64 lowercase letters, digits, '_' or '-'",
+            provider.id
+        )
+    })?;
+    validate_provider_type(&provider.provider)
+        .map_err(|e| format!("Provider '{}': {}", provider.id, e))?;
+    validate_base_url(&provider.base_url)?;
+    if let Some(model) = &provider.model {
+        if model.trim().is_empty() || model.chars().any(char::is_whitespace) {
+            return Err(format!("Provider '{}' has an invalid model '{}'", provider.id, model));
+        }
+    }
+    Ok(())
+}
 
+fn validate_pack_routes(provider_ids: &BTreeSet<String>, pack: &ModelPack) -> Result<(), String> {
+    for role in PACK_ROLES {
+        if let Some(provider_id) = pack.role(role)?.provider_id {
+            if !provider_ids.contains(&provider_id) {
+                return Err(format!(
+                    "{} role is routed to provider '{}', which is not configured",
+                    role, provider_id
+                ));
+            }
+        }
+    }
     Ok(())
 }
 
+// Provider ids a pack routes to that this config does not have, as in a pack file from
+// another machine
+pub fn unresolved_routes(config: &EngineConfig, pack: &ModelPack) -> Vec<String> {
+    let provider_ids: BTreeSet<String> = config.provider_chain().into_iter().map(|p| p.id).collect();
+    let routes = [
+        &pack.analysis_provider,
+        &pack.gossip_provider,
+        &pack.report_provider,
+        &pack.embedding_provider,
+    ];
+
+    let mut unresolved: Vec<String> = Vec::new();
+    for route in routes.into_iter().flatten() {
+        if !provider_ids.contains(route) && !unresolved.contains(route) {
+            unresolved.push(route.clone());
+        }
+    }
+    unresolved
+}
+
+// Drop routes to providers this config does not have, so those roles run on the primary
+// provider (gossip follows analysis as usual). Returns the provider ids that were dropped.
+pub fn clear_unresolved_routes(config: &EngineConfig, pack: &mut ModelPack) -> Vec<String> {
+    let unresolved = unresolved_routes(config, pack);
+    let routes = [
+        &mut pack.analysis_provider,
+        &mut pack.gossip_provider,
+        &mut pack.report_provider,
+        &mut pack.embedding_provider,
+    ];
+    for route in routes {
+        if matches!(route, Some(id) if unresolved.contains(id)) {
+            *route = None;
+        }
+    }
+    unresolved
+}
+
 pub fn get_active_pack(config: &EngineConfig) -> Option<&ModelPack> {
     config.active_pack_id.as_ref()
         .and_then(|id| config.packs.get(id))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_to_unknown_providers_are_cleared_on_import() {
+        let mut config = EngineConfig::default();
+        config.fallback_providers.push(ProviderConfig {
+            id: "cloud".to_string(),
+            provider: "anthropic".to_string(),
+            base_url: "https://api.anthropic.com".to_string(),
+            credential_id: None,
+            model: None,
+        });
+
+        let mut pack = builtin_packs().remove("light_fast").unwrap();
+        pack.analysis_provider = Some("cloud".to_string());
+        pack.report_provider = Some("gpu-box".to_string());
+        pack.embedding_provider = Some("gpu-box".to_string());
+
+        assert_eq!(unresolved_routes(&config, &pack), vec!["gpu-box".to_string()]);
+        assert!(validate_pack_routes(&known_ids(&config), &pack).is_err());
+
+        assert_eq!(clear_unresolved_routes(&config, &mut pack), vec!["gpu-box".to_string()]);
+        assert_eq!(pack.analysis_provider.as_deref(), Some("cloud"));
+        assert_eq!(pack.report_provider, None);
+        assert_eq!(pack.embedding_provider, None);
+        assert!(validate_pack_routes(&known_ids(&config), &pack).is_ok());
+    }
+
+    #[test]
+    fn fallbacks_for_a_routed_role_never_get_its_model_on_another_kind_of_provider() {
+        let fallback = |id: &str, provider: &str, model: Option<&str>| ProviderConfig {
+            id: id.to_string(),
+            provider: provider.to_string(),
+            base_url: "http://127.0.0.1:8080".to_string(),
+            credential_id: None,
+            model: model.map(str::to_string),
+        };
+        let config = EngineConfig {
+            fallback_providers: vec![
+                fallback("cloud", "anthropic", Some("claude-fallback")),
+                fallback("cloud2", "anthropic", None),
+                fallback("lan", "openai_compatible", None),
+                fallback("lan2", "openai_compatible", Some("qwen3-8b")),
+            ],
+            ..EngineConfig::default()
+        };
+        let mut pack = builtin_packs().remove("light_fast").unwrap();
+        pack.analysis_model = "claude-sonnet".to_string();
+        pack.analysis_provider = Some("cloud".to_string());
+
+        let (chain, unusable) = config.provider_chain_for(&pack, &pack.role("analysis").unwrap()).unwrap();
+        let models: Vec<(&str, &str)> = chain
+            .iter()
+            .map(|p| (p.id.as_str(), p.model.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            models,
+            vec![
+                ("cloud", "claude-sonnet"),
+                // The report model still runs on the primary
+                ("primary", "qwen3:4b"),
+                ("cloud2", "claude-sonnet"),
+                ("lan2", "qwen3-8b"),
+            ]
+        );
+        assert_eq!(unusable.len(), 1);
+        assert!(unusable[0].starts_with("lan:"));
+
+        // Unrouted roles send the pack's model wherever no model of its own is set
+        let (chain, unusable) = config.provider_chain_for(&pack, &pack.role("report").unwrap()).unwrap();
+        assert!(unusable.is_empty());
+        assert_eq!(chain[0].model.as_deref(), Some("qwen3:4b"));
+        assert_eq!(chain[2].model.as_deref(), Some("qwen3:4b"));
+    }
+
+    fn known_ids(config: &EngineConfig) -> BTreeSet<String> {
+        config.provider_chain().into_iter().map(|p| p.id).collect()
+    }
+
+    // engine-config.json with `content` in a fresh directory of its own
+    fn config_file(name: &str, content: &serde_json::Value) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dataconfessional-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("engine-config.json");
+        fs::write(&path, serde_json::to_string_pretty(content).unwrap()).unwrap();
+        path
+    }
+
+    fn on_disk(path: &Path) -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn shipped_v1_packs() -> serde_json::Value {
+        serde_json::json!({
+            "light_fast": {
+                "label": "Fast & Light",
+                "analysis_model": "qwen3:4b",
+                "report_model": "qwen3:4b",
+                "embedding_model": "qwen3-embedding:4b"
+            },
+            "analyst_fast": {
+                "label": "Analyst Pack (Recommended)",
+                "analysis_model": "gurubot/glm-4.6v-flash-gguf:q4_k_m",
+                "report_model": "gurubot/glm-4.6v-flash-gguf:q4_k_m",
+                "embedding_model": "qwen3-embedding:4b"
+            }
+        })
+    }
+
+    #[test]
+    fn baseline_v1_config_migrates_to_current() {
+        let v1 = serde_json::json!({
+            "provider": "ollama",
+            "base_url": "http://127.0.0.1:11434",
+            "active_pack_id": "analyst_fast",
+            "packs": shipped_v1_packs()
+        });
+        let path = config_file("migrate-v1", &v1);
+
+        let config = load_config_from(&path).unwrap();
+        assert_eq!(config.schema_version, CONFIG_SCHEMA_VERSION);
+        assert_eq!(config.credential_id, None);
+        assert!(config.pinned_packs.is_empty());
+        assert!(config.fallback_providers.is_empty());
+        for (id, pack) in builtin_packs() {
+            assert_eq!(config.packs.get(&id), Some(&pack), "{}", id);
+        }
+
+        // The untouched original is kept, and the rewritten file no longer freezes the built-ins
+        let backup = path.with_file_name("engine-config.v1.json.bak");
+        assert_eq!(on_disk(&backup), v1);
+        let written = on_disk(&path);
+        assert_eq!(written["schema_version"], CONFIG_SCHEMA_VERSION);
+        assert_eq!(written["packs"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn hand_edited_builtin_in_v2_is_pinned() {
+        let mut packs = shipped_v1_packs();
+        packs["light_fast"]["analysis_model"] = "llama3.1:8b".into();
+        packs["my_pack"] = serde_json::json!({
+            "label": "Mine",
+            "analysis_model": "mistral:7b",
+            "report_model": "mistral:7b",
+            "embedding_model": "nomic-embed-text"
+        });
+        let v2 = serde_json::json!({
+            "schema_version": 2,
+            "provider": "ollama",
+            "base_url": "http://127.0.0.1:11434",
+            "credential_id": null,
+            "api_key_env": null,
+            "api_key_file": null,
+            "active_pack_id": "light_fast",
+            "packs": packs
+        });
+        let path = config_file("migrate-v2", &v2);
+
+        let config = load_config_from(&path).unwrap();
+        assert_eq!(config.pinned_packs, BTreeSet::from(["light_fast".to_string()]));
+        assert_eq!(config.packs["light_fast"].analysis_model, "llama3.1:8b");
+        assert_eq!(config.packs["analyst_fast"], builtin_packs()["analyst_fast"]);
+        assert_eq!(config.packs["my_pack"].label, "Mine");
+        assert!(path.with_file_name("engine-config.v2.json.bak").exists());
+
+        let written = on_disk(&path);
+        assert!(written["packs"].get("light_fast").is_some());
+        assert!(written["packs"].get("analyst_fast").is_none());
+        assert!(written["packs"].get("my_pack").is_some());
+    }
+
+    #[test]
+    fn v3_config_gains_an_empty_fallback_chain() {
+        let v3 = serde_json::json!({
+            "schema_version": 3,
+            "provider": "ollama",
+            "base_url": "http://127.0.0.1:11434",
+            "credential_id": null,
+            "api_key_env": null,
+            "api_key_file": null,
+            "active_pack_id": "analyst_fast",
+            "packs": {},
+            "pinned_packs": []
+        });
+        let path = config_file("migrate-v3", &v3);
+
+        let config = load_config_from(&path).unwrap();
+        assert!(config.fallback_providers.is_empty());
+        assert_eq!(config.active_pack_id.as_deref(), Some("analyst_fast"));
+        assert_eq!(on_disk(&path.with_file_name("engine-config.v3.json.bak")), v3);
+        assert_eq!(on_disk(&path)["fallback_providers"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn config_from_a_newer_app_is_refused() {
+        let future = serde_json::json!({
+            "schema_version": CONFIG_SCHEMA_VERSION + 1,
+            "provider": "ollama",
+            "base_url": "http://127.0.0.1:11434",
+            "packs": {}
+        });
+        assert!(migrate_config(future.clone()).is_err());
+
+        let path = config_file("migrate-future", &future);
+        assert!(load_config_from(&path).is_err());
+        // Left as it was for the newer app
+        assert_eq!(on_disk(&path), future);
+    }
+
+    #[test]
+    fn unknown_provider_types_are_rejected() {
+        let config = EngineConfig {
+            provider: "olama".to_string(),
+            ..EngineConfig::default()
+        };
+        assert!(validate_config(&config).is_err());
+
+        let mut config = EngineConfig::default();
+        config.fallback_providers.push(ProviderConfig {
+            id: "backup".to_string(),
+            provider: "openai".to_string(),
+            base_url: "http://127.0.0.1:8080".to_string(),
+            credential_id: None,
+            model: None,
+        });
+        assert!(validate_config(&config).is_err());
+
+        for provider in PROVIDER_TYPES {
+            config.fallback_providers[0].provider = provider.to_string();
+            assert!(validate_config(&config).is_ok(), "{}", provider);
+        }
+    }
+
+    #[test]
+    fn startup_and_reload_agree_on_an_invalid_file() {
+        let invalid = serde_json::json!({
+            "schema_version": CONFIG_SCHEMA_VERSION,
+            "provider": "ollama",
+            "base_url": "http://127.0.0.1:11434",
+            "active_pack_id": "does_not_exist",
+            "packs": {},
+            "pinned_packs": [],
+            "fallback_providers": []
+        });
+        let path = config_file("invalid-config", &invalid);
+
+        assert!(read_config(&path).is_err());
+        assert!(load_config_from(&path).is_err());
+
+        // With a known-good copy, startup restores it instead
+        let mut valid = invalid.clone();
+        valid["active_pack_id"] = "light_fast".into();
+        fs::write(backup_path(&path), valid.to_string()).unwrap();
+        let config = load_config_from(&path).unwrap();
+        assert_eq!(config.active_pack_id.as_deref(), Some("light_fast"));
+        assert!(read_config(&path).is_ok());
+    }
+}
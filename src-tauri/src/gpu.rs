@@ -0,0 +1,466 @@
+// GPU detection for the engine health report
+// Linux reads /sys/class/drm and PCI vendor ids; nvidia-smi fills in NVIDIA details on any platform
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// PCI vendor ids of the GPU makers we name
+const VENDOR_NVIDIA: &str = "0x10de";
+const VENDOR_AMD: &str = "0x1002";
+const VENDOR_INTEL: &str = "0x8086";
+
+// Shown in place of a vendor when no GPU is found
+pub const CPU_ONLY: &str = "CPU only";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuInfo {
+    pub vendor: String, // "nvidia" | "amd" | "intel" | "apple" | "unknown"
+    pub name: Option<String>,
+    // Dedicated memory; None for integrated GPUs and drivers that do not report it
+    pub vram_mb: Option<u64>,
+    pub driver: Option<String>,
+    pub driver_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuSummary {
+    // Vendor of the GPU with the most VRAM, or "none"
+    pub vendor: String,
+    pub vram_gb: Option<u32>,
+    pub gpus: Vec<GpuInfo>,
+    pub cpu_only: bool,
+    // "NVIDIA GeForce RTX 3090 (24 GB)" or "CPU only"
+    pub description: String,
+}
+
+impl GpuSummary {
+    pub fn from_gpus(gpus: Vec<GpuInfo>) -> Self {
+        let best = gpus.iter().max_by_key(|gpu| gpu.vram_mb.unwrap_or(0));
+
+        let description = match best {
+            Some(gpu) => {
+                let name = gpu.name.clone().unwrap_or_else(|| vendor_label(&gpu.vendor).to_string());
+                match gpu.vram_mb {
+                    Some(mb) => format!("{} ({} GB)", name, mb_to_gb(mb)),
+                    None => name,
+                }
+            }
+            None => CPU_ONLY.to_string(),
+        };
+
+        GpuSummary {
+            vendor: best.map(|gpu| gpu.vendor.clone()).unwrap_or_else(|| "none".to_string()),
+            vram_gb: best.and_then(|gpu| gpu.vram_mb).map(mb_to_gb),
+            description,
+            cpu_only: gpus.is_empty(),
+            gpus,
+        }
+    }
+}
+
+// Everything we can find on this machine. Runs external commands, so call it off the async runtime.
+pub fn detect_gpus() -> GpuSummary {
+    let mut gpus = if cfg!(target_os = "linux") {
+        scan_sysfs(Path::new("/"))
+    } else {
+        Vec::new()
+    };
+
+    // nvidia-smi knows the marketing name and VRAM, which sysfs does not for the proprietary driver
+    let wants_nvidia_smi = cfg!(not(target_os = "linux")) || gpus.iter().any(|gpu| gpu.vendor == "nvidia");
+    if wants_nvidia_smi {
+        if let Some(output) = run_nvidia_smi() {
+            merge_nvidia_smi(&mut gpus, parse_nvidia_smi(&output));
+        }
+    }
+
+    // Apple Silicon has no sysfs or discrete VRAM; its GPU shares system memory
+    if cfg!(all(target_os = "macos", target_arch = "aarch64")) && gpus.is_empty() {
+        gpus.push(GpuInfo {
+            vendor: "apple".to_string(),
+            name: Some("Apple Silicon GPU".to_string()),
+            vram_mb: None,
+            driver: Some("metal".to_string()),
+            driver_version: None,
+        });
+    }
+
+    GpuSummary::from_gpus(gpus)
+}
+
+// Display adapters under `root`/sys/class/drm. `root` is "/" on a real system and a fixture
+// directory in tests, laid out the same way (including usr/share/hwdata/pci.ids if names are wanted).
+pub fn scan_sysfs(root: &Path) -> Vec<GpuInfo> {
+    let drm = root.join("sys/class/drm");
+    let entries = match fs::read_dir(&drm) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    // Only cardN is a GPU; cardN-HDMI-A-1 and renderDN are its connectors and render nodes
+    let mut cards: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| {
+            name.strip_prefix("card")
+                .map(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+                .unwrap_or(false)
+        })
+        .collect();
+    cards.sort_by_key(|name| name[4..].parse::<u32>().unwrap_or(u32::MAX));
+
+    let pci_ids = read_pci_ids(root);
+    let mut seen_slots = Vec::new();
+    let mut gpus = Vec::new();
+
+    for card in cards {
+        let device = drm.join(&card).join("device");
+        let vendor_id = match read_trimmed(&device.join("vendor")) {
+            Some(id) => id.to_ascii_lowercase(),
+            None => continue,
+        };
+
+        // Two cards can share one PCI device (e.g. simpledrm handing over); count it once
+        if let Some(slot) = uevent_value(&device, "PCI_SLOT_NAME") {
+            if seen_slots.contains(&slot) {
+                continue;
+            }
+            seen_slots.push(slot);
+        }
+
+        let device_id = read_trimmed(&device.join("device")).map(|id| id.to_ascii_lowercase());
+        let driver = uevent_value(&device, "DRIVER").or_else(|| {
+            fs::read_link(device.join("driver"))
+                .ok()
+                .and_then(|link| link.file_name().map(|n| n.to_string_lossy().to_string()))
+        });
+        let driver_version = driver
+            .as_ref()
+            .and_then(|driver| read_trimmed(&root.join("sys/module").join(driver).join("version")));
+
+        // amdgpu reports its own marketing name and VRAM size
+        let name = read_trimmed(&device.join("product_name"))
+            .or_else(|| {
+                device_id
+                    .as_ref()
+                    .and_then(|id| pci_ids.as_deref().and_then(|ids| pci_device_name(ids, &vendor_id, id)))
+            });
+        let vram_mb = read_trimmed(&device.join("mem_info_vram_total"))
+            .and_then(|bytes| bytes.parse::<u64>().ok())
+            .filter(|bytes| *bytes > 0)
+            .map(|bytes| bytes / (1024 * 1024));
+
+        gpus.push(GpuInfo {
+            vendor: vendor_from_pci(&vendor_id).to_string(),
+            name,
+            vram_mb,
+            driver,
+            driver_version,
+        });
+    }
+
+    gpus
+}
+
+// Output of `nvidia-smi --query-gpu=name,memory.total,driver_version --format=csv,noheader,nounits`
+pub fn parse_nvidia_smi(output: &str) -> Vec<GpuInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != 3 || fields[0].is_empty() {
+                return None;
+            }
+            Some(GpuInfo {
+                vendor: "nvidia".to_string(),
+                name: Some(fields[0].to_string()),
+                vram_mb: fields[1].parse().ok(),
+                driver: Some("nvidia".to_string()),
+                driver_version: Some(fields[2].to_string()).filter(|v| !v.is_empty()),
+            })
+        })
+        .collect()
+}
+
+// Replace sysfs NVIDIA entries with nvidia-smi's, in order; extra nvidia-smi GPUs are added
+fn merge_nvidia_smi(gpus: &mut Vec<GpuInfo>, from_smi: Vec<GpuInfo>) {
+    let mut from_smi = from_smi.into_iter();
+    for gpu in gpus.iter_mut().filter(|gpu| gpu.vendor == "nvidia") {
+        match from_smi.next() {
+            Some(detail) => *gpu = detail,
+            None => return,
+        }
+    }
+    gpus.extend(from_smi);
+}
+
+fn run_nvidia_smi() -> Option<String> {
+    let output = Command::new("nvidia-smi")
+        .args([
+            "--query-gpu=name,memory.total,driver_version",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        None
+    }
+}
+
+fn vendor_from_pci(vendor_id: &str) -> &'static str {
+    match vendor_id {
+        VENDOR_NVIDIA => "nvidia",
+        VENDOR_AMD => "amd",
+        VENDOR_INTEL => "intel",
+        _ => "unknown",
+    }
+}
+
+fn vendor_label(vendor: &str) -> &str {
+    match vendor {
+        "nvidia" => "NVIDIA GPU",
+        "amd" => "AMD GPU",
+        "intel" => "Intel GPU",
+        "apple" => "Apple GPU",
+        _ => "Unknown GPU",
+    }
+}
+
+fn mb_to_gb(mb: u64) -> u32 {
+    ((mb + 512) / 1024) as u32
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn uevent_value(device: &Path, key: &str) -> Option<String> {
+    let uevent = fs::read_to_string(device.join("uevent")).ok()?;
+    uevent.lines().find_map(|line| {
+        line.strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix('='))
+            .map(str::to_string)
+    })
+}
+
+// The pciutils database, wherever the distribution keeps it
+fn read_pci_ids(root: &Path) -> Option<String> {
+    ["usr/share/hwdata/pci.ids", "usr/share/misc/pci.ids", "usr/share/pci.ids"]
+        .iter()
+        .find_map(|path| fs::read_to_string(root.join(path)).ok())
+}
+
+// pci.ids lists a vendor as "10de  NVIDIA Corporation" and its devices below it as
+// "\t2204  GA102 [GeForce RTX 3090]"
+fn pci_device_name(pci_ids: &str, vendor_id: &str, device_id: &str) -> Option<String> {
+    let vendor = vendor_id.trim_start_matches("0x");
+    let device = device_id.trim_start_matches("0x");
+    let mut in_vendor = false;
+
+    for line in pci_ids.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if !line.starts_with('\t') {
+            if in_vendor {
+                return None;
+            }
+            in_vendor = line.to_ascii_lowercase().starts_with(&format!("{}  ", vendor));
+        } else if in_vendor && !line.starts_with("\t\t") {
+            let line = line.trim_start_matches('\t');
+            if line.to_ascii_lowercase().starts_with(&format!("{}  ", device)) {
+                return Some(line[device.len()..].trim().to_string());
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // A fresh root for one test, laid out like the real filesystem
+    fn fixture_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir()
+            .join(format!("dataconfessional-gpu-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sys/class/drm")).unwrap();
+        root
+    }
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    // cardN/device with the given PCI ids and uevent; extra files are (name, content) pairs
+    fn add_card(
+        root: &Path,
+        card: &str,
+        vendor: &str,
+        device: &str,
+        uevent: &str,
+        extra: &[(&str, &str)],
+    ) {
+        let dir = format!("sys/class/drm/{}/device", card);
+        write(root, &format!("{}/vendor", dir), &format!("{}\n", vendor));
+        write(root, &format!("{}/device", dir), &format!("{}\n", device));
+        write(root, &format!("{}/uevent", dir), uevent);
+        for (name, content) in extra {
+            write(root, &format!("{}/{}", dir, name), content);
+        }
+    }
+
+    const PCI_IDS: &str = "\
+# pci.ids excerpt
+10de  NVIDIA Corporation
+\t2204  GA102 [GeForce RTX 3090]
+\t\t10de 147d  GA102 [GeForce RTX 3090 Founders Edition]
+\t2684  AD102 [GeForce RTX 4090]
+1002  Advanced Micro Devices, Inc. [AMD/ATI]
+\t744c  Navi 31 [Radeon RX 7900 XT/7900 XTX]
+";
+
+    #[test]
+    fn nvidia_card_is_named_from_pci_ids() {
+        let root = fixture_root("nvidia");
+        write(&root, "usr/share/hwdata/pci.ids", PCI_IDS);
+        write(&root, "sys/module/nvidia/version", "550.54.14\n");
+        add_card(
+            &root,
+            "card0",
+            "0x10de",
+            "0x2204",
+            "DRIVER=nvidia\nPCI_CLASS=30000\nPCI_SLOT_NAME=0000:01:00.0\n",
+            &[],
+        );
+
+        let gpus = scan_sysfs(&root);
+
+        assert_eq!(
+            gpus,
+            vec![GpuInfo {
+                vendor: "nvidia".to_string(),
+                name: Some("GA102 [GeForce RTX 3090]".to_string()),
+                // The proprietary driver does not expose VRAM in sysfs; nvidia-smi fills it in
+                vram_mb: None,
+                driver: Some("nvidia".to_string()),
+                driver_version: Some("550.54.14".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn amdgpu_reports_its_own_name_and_vram() {
+        let root = fixture_root("amdgpu");
+        write(&root, "usr/share/hwdata/pci.ids", PCI_IDS);
+        add_card(
+            &root,
+            "card1",
+            "0x1002",
+            "0x744c",
+            "DRIVER=amdgpu\nPCI_SLOT_NAME=0000:03:00.0\n",
+            &[
+                ("product_name", "AMD Radeon RX 7900 XTX\n"),
+                ("mem_info_vram_total", "25753026560\n"),
+            ],
+        );
+
+        let gpus = scan_sysfs(&root);
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].vendor, "amd");
+        assert_eq!(gpus[0].name.as_deref(), Some("AMD Radeon RX 7900 XTX"));
+        assert_eq!(gpus[0].vram_mb, Some(24560));
+        assert_eq!(gpus[0].driver.as_deref(), Some("amdgpu"));
+
+        let summary = GpuSummary::from_gpus(gpus);
+        assert_eq!(summary.vram_gb, Some(24));
+        assert_eq!(summary.description, "AMD Radeon RX 7900 XTX (24 GB)");
+        assert!(!summary.cpu_only);
+    }
+
+    #[test]
+    fn cards_sharing_a_pci_function_count_once() {
+        let root = fixture_root("duplicate");
+        let uevent = "DRIVER=i915\nPCI_SLOT_NAME=0000:00:02.0\n";
+        add_card(&root, "card0", "0x8086", "0x46a6", uevent, &[]);
+        add_card(&root, "card1", "0x8086", "0x46a6", uevent, &[]);
+        // Connectors and render nodes are not GPUs of their own
+        add_card(&root, "card0-eDP-1", "0x8086", "0x46a6", uevent, &[]);
+        add_card(&root, "renderD128", "0x8086", "0x46a6", uevent, &[]);
+
+        let gpus = scan_sysfs(&root);
+
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].vendor, "intel");
+        // This fixture has no pci.ids to name it from
+        assert_eq!(gpus[0].name, None);
+    }
+
+    #[test]
+    fn no_drm_directory_means_no_gpus() {
+        let root = fixture_root("empty");
+        fs::remove_dir_all(root.join("sys")).unwrap();
+
+        assert!(scan_sysfs(&root).is_empty());
+        assert_eq!(GpuSummary::from_gpus(Vec::new()).description, CPU_ONLY);
+    }
+
+    #[test]
+    fn nvidia_smi_csv_is_parsed() {
+        // nvidia-smi --query-gpu=name,memory.total,driver_version --format=csv,noheader,nounits
+        let output = "NVIDIA GeForce RTX 3090, 24576, 550.54.14\n\
+                      NVIDIA RTX A4000, 16376, 550.54.14\n\
+                      NVIDIA GeForce GTX 1650, [N/A], 470.239.06\n\
+                      \n";
+
+        let gpus = parse_nvidia_smi(output);
+
+        assert_eq!(gpus.len(), 3);
+        assert_eq!(gpus[0].name.as_deref(), Some("NVIDIA GeForce RTX 3090"));
+        assert_eq!(gpus[0].vram_mb, Some(24576));
+        assert_eq!(gpus[0].driver_version.as_deref(), Some("550.54.14"));
+        assert_eq!(gpus[1].vram_mb, Some(16376));
+        assert_eq!(gpus[2].vram_mb, None);
+        assert_eq!(GpuSummary::from_gpus(gpus).description, "NVIDIA GeForce RTX 3090 (24 GB)");
+    }
+
+    #[test]
+    fn nvidia_smi_details_replace_sysfs_entries() {
+        let mut gpus = vec![
+            GpuInfo {
+                vendor: "intel".to_string(),
+                name: None,
+                vram_mb: None,
+                driver: Some("i915".to_string()),
+                driver_version: None,
+            },
+            GpuInfo {
+                vendor: "nvidia".to_string(),
+                name: Some("GA102 [GeForce RTX 3090]".to_string()),
+                vram_mb: None,
+                driver: Some("nvidia".to_string()),
+                driver_version: None,
+            },
+        ];
+
+        merge_nvidia_smi(&mut gpus, parse_nvidia_smi("NVIDIA GeForce RTX 3090, 24576, 550.54.14\n"));
+
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].vendor, "intel");
+        assert_eq!(gpus[1].name.as_deref(), Some("NVIDIA GeForce RTX 3090"));
+        assert_eq!(gpus[1].vram_mb, Some(24576));
+    }
+}
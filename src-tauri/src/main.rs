@@ -1,43 +1,113 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod anthropic;
 mod api_key;
+mod app_paths;
+mod config_state;
+mod diagnostics;
 mod engine;
 mod engine_config;
+mod gpu;
+#[cfg(test)]
+mod mock_server;
+mod ollama;
+mod openai_compatible;
+mod policy;
+mod provider;
+mod recommend;
+mod system_info;
 
-use tauri::Manager;
+use tauri::{Manager, State};
+use zeroize::Zeroizing;
+
+use config_state::ConfigState;
+
+fn credential_id_or_default(credential_id: Option<String>) -> String {
+    credential_id.unwrap_or_else(|| api_key::DEFAULT_CREDENTIAL_ID.to_string())
+}
 
 #[tauri::command]
-fn store_api_key(key: String) -> Result<(), String> {
-    api_key::store_api_key(&key)
+fn store_api_key(key: Zeroizing<String>, credential_id: Option<String>) -> Result<(), String> {
+    api_key::store_api_key(&credential_id_or_default(credential_id), &key)
 }
 
 #[tauri::command]
-fn get_api_key() -> Result<String, String> {
-    api_key::get_api_key()
+fn get_api_key_info(
+    credential_id: Option<String>,
+    state: State<'_, ConfigState>,
+) -> Result<api_key::ApiKeyInfo, String> {
+    let key_sources = api_key::KeySources::from_config(&state.get()?);
+    api_key::get_api_key_info(&credential_id_or_default(credential_id), &key_sources)
 }
 
 #[tauri::command]
-fn has_api_key() -> bool {
-    api_key::has_api_key()
+fn has_api_key(credential_id: Option<String>, state: State<'_, ConfigState>) -> Result<bool, String> {
+    let key_sources = api_key::KeySources::from_config(&state.get()?);
+    Ok(api_key::has_api_key(&credential_id_or_default(credential_id), &key_sources))
 }
 
 #[tauri::command]
-fn delete_api_key() -> Result<(), String> {
-    api_key::delete_api_key()
+fn delete_api_key(credential_id: Option<String>) -> Result<(), String> {
+    api_key::delete_api_key(&credential_id_or_default(credential_id))
+}
+
+#[tauri::command]
+fn list_api_keys() -> Result<Vec<String>, String> {
+    api_key::list_credential_ids()
+}
+
+#[tauri::command]
+fn set_api_key_expiry(credential_id: Option<String>, expires_at: Option<u64>) -> Result<(), String> {
+    api_key::set_expiry(&credential_id_or_default(credential_id), expires_at)
+}
+
+#[tauri::command]
+fn set_vault_passphrase(passphrase: Option<Zeroizing<String>>) {
+    api_key::set_vault_passphrase(passphrase)
 }
 
 fn main() {
+  app_paths::init_from_args(std::env::args().skip(1));
+
   tauri::Builder::default()
+    .manage(ConfigState::default())
+    .setup(|app| {
+      // Live reload is a convenience; without it edits still apply after a restart
+      let handle = app.handle();
+      if let Err(e) = app.state::<ConfigState>().watch(handle) {
+        eprintln!("{}", e);
+      }
+      Ok(())
+    })
     .invoke_handler(tauri::generate_handler![
       store_api_key,
-      get_api_key,
+      get_api_key_info,
       has_api_key,
       delete_api_key,
+      list_api_keys,
+      set_api_key_expiry,
+      set_vault_passphrase,
       engine::engine_health,
+      engine::engine_diagnostics,
+      engine::engine_recommend_pack,
+      engine::engine_get_config,
       engine::engine_install_pack,
+      engine::engine_list_packs,
+      engine::engine_create_pack,
+      engine::engine_update_pack,
+      engine::engine_delete_pack,
+      engine::engine_set_active_pack,
+      engine::engine_pin_pack,
+      engine::engine_unpin_pack,
+      engine::engine_export_packs,
+      engine::engine_preview_pack_import,
+      engine::engine_import_packs,
       engine::engine_chat,
       engine::engine_generate_report,
+      engine::validate_api_key,
+      engine::rotate_api_key,
+      engine::anthropic_messages,
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -0,0 +1,82 @@
+// Local HTTP server for provider tests
+// Answers a fixed list of requests in order and hands back what it was sent
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    pub fn json(status: u16, body: &str) -> Self {
+        Response {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    pub fn sse(body: &str) -> Self {
+        Response {
+            status: 200,
+            content_type: "text/event-stream",
+            body: body.to_string(),
+        }
+    }
+}
+
+// Base URL to point a provider at, and a handle that yields each raw request (head and body)
+pub fn serve(responses: Vec<Response>) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+    let base_url = format!("http://{}", listener.local_addr().expect("mock server address"));
+
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (stream, _) = listener.accept().expect("accept mock request");
+            let mut reader = BufReader::new(stream);
+
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+                request.push_str(&line);
+            }
+            let mut body = vec![0u8; content_length];
+            let _ = reader.read_exact(&mut body);
+            request.push_str("\r\n");
+            request.push_str(&String::from_utf8_lossy(&body));
+            requests.push(request);
+
+            let reply = format!(
+                "HTTP/1.1 {} Mock\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.status,
+                response.content_type,
+                response.body.len(),
+                response.body
+            );
+            let _ = reader.get_mut().write_all(reply.as_bytes());
+        }
+        requests
+    });
+
+    (base_url, handle)
+}
+
+// A URL nothing listens on
+pub fn closed_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+    format!("http://{}", listener.local_addr().expect("mock server address"))
+}
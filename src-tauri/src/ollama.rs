@@ -0,0 +1,280 @@
+// Ollama engine provider
+// Talks to a local or LAN Ollama server through its native /api endpoints
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::engine_config::GenerationSettings;
+use crate::provider::{ChunkHandler, CompletionRequest, EngineProvider, LoadedModel};
+
+const OLLAMA_TIMEOUT: Duration = Duration::from_secs(5);
+const CHAT_TIMEOUT: Duration = Duration::from_secs(90);
+const COMPLETION_TIMEOUT: Duration = Duration::from_secs(120);
+// Model downloads can be several gigabytes
+const PULL_TIMEOUT: Duration = Duration::from_secs(300);
+
+pub struct OllamaProvider {
+    base_url: String,
+}
+
+impl OllamaProvider {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn client(&self, timeout: Duration) -> Result<reqwest::Client, String> {
+        reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))
+    }
+
+    // Ollama's native chat API, which honors num_ctx and top_k
+    async fn post_chat(
+        &self,
+        request: &CompletionRequest,
+        stream: bool,
+        timeout: Duration,
+    ) -> Result<reqwest::Response, String> {
+        let messages: Vec<serde_json::Value> = request
+            .messages
+            .iter()
+            .map(|m| serde_json::json!({ "role": m.role, "content": m.content }))
+            .collect();
+
+        let chat_body = serde_json::json!({
+            "model": request.model,
+            "messages": messages,
+            "stream": stream,
+            "options": ollama_options(&request.settings),
+        });
+
+        let response = self
+            .client(timeout)?
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&chat_body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to call Ollama: {}", e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Ollama error: {}", error_text));
+        }
+
+        Ok(response)
+    }
+
+    async fn get_json(&self, path: &str) -> Result<serde_json::Value, String> {
+        let response = self
+            .client(OLLAMA_TIMEOUT)?
+            .get(format!("{}{}", self.base_url, path))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Ollama returned status: {}", response.status()));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Ollama response: {}", e))
+    }
+
+    // Installed models as /api/tags lists them, with name and size
+    async fn tags(&self) -> Result<Vec<serde_json::Value>, String> {
+        let json = self.get_json("/api/tags").await?;
+
+        json.get("models")
+            .and_then(|m| m.as_array())
+            .cloned()
+            .ok_or("Invalid response format from Ollama".to_string())
+    }
+}
+
+#[async_trait]
+impl EngineProvider for OllamaProvider {
+    async fn health(&self) -> Result<(), String> {
+        let unavailable = || "Ollama is not available. Please install and start Ollama first.".to_string();
+
+        let response = self
+            .client(OLLAMA_TIMEOUT)?
+            .get(format!("{}/api/tags", self.base_url))
+            .send()
+            .await
+            .map_err(|_| unavailable())?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(unavailable())
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, String> {
+        Ok(self
+            .tags()
+            .await?
+            .iter()
+            .filter_map(|m| m.get("name").and_then(|n| n.as_str()).map(|s| s.to_string()))
+            .collect())
+    }
+
+    async fn model_sizes(&self) -> Result<BTreeMap<String, u64>, String> {
+        Ok(self
+            .tags()
+            .await?
+            .iter()
+            .filter_map(|m| {
+                let name = m.get("name").and_then(|n| n.as_str())?;
+                let size = m.get("size").and_then(|s| s.as_u64())?;
+                Some((name.to_string(), size))
+            })
+            .collect())
+    }
+
+    async fn version(&self) -> Result<Option<String>, String> {
+        let json = self.get_json("/api/version").await?;
+        Ok(json.get("version").and_then(|v| v.as_str()).map(|v| v.to_string()))
+    }
+
+    async fn loaded_models(&self) -> Result<Vec<LoadedModel>, String> {
+        let json = self.get_json("/api/ps").await?;
+        let models = json
+            .get("models")
+            .and_then(|m| m.as_array())
+            .ok_or("Invalid response format from Ollama")?;
+
+        Ok(models
+            .iter()
+            .filter_map(|m| {
+                Some(LoadedModel {
+                    name: m.get("name").and_then(|n| n.as_str())?.to_string(),
+                    size_bytes: m.get("size").and_then(|s| s.as_u64()),
+                    vram_bytes: m.get("size_vram").and_then(|s| s.as_u64()),
+                })
+            })
+            .collect())
+    }
+
+    async fn install_model(&self, model: &str) -> Result<(), String> {
+        let pull_body = serde_json::json!({
+            "name": model,
+            "stream": false
+        });
+
+        let response = self
+            .client(PULL_TIMEOUT)?
+            .post(format!("{}/api/pull", self.base_url))
+            .json(&pull_body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to pull model {}: {}", model, e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to pull model {}: {}", model, response.status()));
+        }
+
+        Ok(())
+    }
+
+    async fn chat_stream(
+        &self,
+        request: CompletionRequest,
+        on_chunk: &mut ChunkHandler<'_>,
+    ) -> Result<String, String> {
+        let response = self.post_chat(&request, true, CHAT_TIMEOUT).await?;
+
+        // One JSON object per line, which may span network chunks
+        let mut full_content = String::new();
+        let mut pending: Vec<u8> = Vec::new();
+        let mut stream = response.bytes_stream();
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| format!("Stream error: {}", e))?;
+            pending.extend_from_slice(&chunk);
+
+            while let Some(newline) = pending.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=newline).collect();
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+
+                let json: serde_json::Value = match serde_json::from_str(line) {
+                    Ok(json) => json,
+                    Err(_) => continue,
+                };
+
+                if let Some(error) = json.get("error").and_then(|e| e.as_str()) {
+                    return Err(format!("Ollama error: {}", error));
+                }
+
+                if let Some(content) = json
+                    .get("message")
+                    .and_then(|m| m.get("content"))
+                    .and_then(|c| c.as_str())
+                {
+                    if content.is_empty() {
+                        continue;
+                    }
+                    full_content.push_str(content);
+                    on_chunk(content)?;
+                }
+            }
+        }
+
+        Ok(full_content)
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<String, String> {
+        let response = self.post_chat(&request, false, COMPLETION_TIMEOUT).await?;
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        json.get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .map(|c| c.to_string())
+            .ok_or("Invalid response format".to_string())
+    }
+}
+
+// Map generation settings onto Ollama's option names, leaving unset ones to the model
+fn ollama_options(settings: &GenerationSettings) -> serde_json::Value {
+    let mut options = serde_json::Map::new();
+
+    if let Some(temperature) = settings.temperature {
+        options.insert("temperature".to_string(), temperature.into());
+    }
+    if let Some(top_p) = settings.top_p {
+        options.insert("top_p".to_string(), top_p.into());
+    }
+    if let Some(top_k) = settings.top_k {
+        options.insert("top_k".to_string(), top_k.into());
+    }
+    if let Some(max_tokens) = settings.max_tokens {
+        options.insert("num_predict".to_string(), max_tokens.into());
+    }
+    if let Some(num_ctx) = settings.num_ctx {
+        options.insert("num_ctx".to_string(), num_ctx.into());
+    }
+    if !settings.stop.is_empty() {
+        options.insert("stop".to_string(), settings.stop.clone().into());
+    }
+    if let Some(seed) = settings.seed {
+        options.insert("seed".to_string(), seed.into());
+    }
+
+    serde_json::Value::Object(options)
+}
@@ -0,0 +1,428 @@
+// OpenAI-compatible engine provider
+// For servers that speak /v1/models and /v1/chat/completions: llama.cpp server, vLLM, LM Studio
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use reqwest::header::AUTHORIZATION;
+use std::time::Duration;
+
+use crate::api_key::{self, KeySources};
+use crate::provider::{bearer_header, ChunkHandler, CompletionRequest, EngineProvider};
+
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+const CHAT_TIMEOUT: Duration = Duration::from_secs(90);
+const COMPLETION_TIMEOUT: Duration = Duration::from_secs(120);
+
+pub struct OpenAiCompatibleProvider {
+    base_url: String,
+    // Only sent when the config names a credential, so no stored key goes to a server
+    // that was never meant to receive it
+    credential_id: Option<String>,
+    key_sources: KeySources,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(base_url: &str, credential_id: Option<&str>, key_sources: &KeySources) -> Self {
+        // Accept the server root or its /v1 prefix
+        let base_url = base_url.trim_end_matches('/');
+        let base_url = base_url.strip_suffix("/v1").unwrap_or(base_url);
+
+        Self {
+            base_url: base_url.to_string(),
+            credential_id: credential_id.map(str::to_string),
+            key_sources: key_sources.clone(),
+        }
+    }
+
+    fn client(&self, timeout: Duration) -> Result<reqwest::Client, String> {
+        reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))
+    }
+
+    // `record_use` for requests that spend the key; health checks and listing don't count
+    fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        timeout: Duration,
+        record_use: bool,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let request = self
+            .client(timeout)?
+            .request(method, format!("{}{}", self.base_url, path));
+
+        match &self.credential_id {
+            Some(credential_id) => {
+                let key = if record_use {
+                    api_key::use_api_key(credential_id, &self.key_sources)
+                } else {
+                    api_key::get_api_key(credential_id, &self.key_sources)
+                };
+                let key = key?.ok_or_else(|| {
+                    format!(
+                        "No API key stored as '{}'. Please configure it in settings.",
+                        credential_id
+                    )
+                })?;
+                Ok(request.header(AUTHORIZATION, bearer_header(&key)))
+            }
+            None => Ok(request),
+        }
+    }
+
+    async fn post_chat(
+        &self,
+        request: &CompletionRequest,
+        stream: bool,
+        timeout: Duration,
+    ) -> Result<reqwest::Response, String> {
+        let response = self
+            .request(reqwest::Method::POST, "/v1/chat/completions", timeout, true)?
+            .json(&chat_body(request, stream))
+            .send()
+            .await
+            .map_err(|e| format!("Could not reach the server at {}: {}", self.base_url, e))?;
+
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl EngineProvider for OpenAiCompatibleProvider {
+    async fn health(&self) -> Result<(), String> {
+        self.list_models().await.map(|_| ())
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, String> {
+        let response = self
+            .request(reqwest::Method::GET, "/v1/models", HEALTH_TIMEOUT, false)?
+            .send()
+            .await
+            .map_err(|e| format!("Could not reach the server at {}: {}", self.base_url, e))?;
+
+        // Error bodies are often plain text or HTML, so check the status before decoding
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
+        }
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse server response: {}", e))?;
+
+        let models = json
+            .get("data")
+            .and_then(|d| d.as_array())
+            .ok_or("Invalid response format from server")?;
+
+        Ok(models
+            .iter()
+            .filter_map(|m| m.get("id").and_then(|id| id.as_str()).map(|s| s.to_string()))
+            .collect())
+    }
+
+    // The key being checked replaces any stored one
+    async fn check_key(&self, key: &str) -> Result<Option<u16>, String> {
+        let response = self
+            .client(HEALTH_TIMEOUT)?
+            .get(format!("{}/v1/models", self.base_url))
+            .header(AUTHORIZATION, bearer_header(key))
+            .send()
+            .await
+            .map_err(|e| format!("Could not reach the server at {}: {}", self.base_url, e))?;
+
+        Ok(Some(response.status().as_u16()))
+    }
+
+    // These servers load models themselves; all the app can do is check it is served
+    async fn install_model(&self, model: &str) -> Result<(), String> {
+        if self.list_models().await?.iter().any(|m| m == model) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Model {} is not loaded on the server at {}. Load it in the server itself; this provider has no install step.",
+                model, self.base_url
+            ))
+        }
+    }
+
+    async fn chat_stream(
+        &self,
+        request: CompletionRequest,
+        on_chunk: &mut ChunkHandler<'_>,
+    ) -> Result<String, String> {
+        let response = self.post_chat(&request, true, CHAT_TIMEOUT).await?;
+
+        // Server-sent events: one `data:` line per delta, ending with `data: [DONE]`
+        let mut full_content = String::new();
+        let mut pending: Vec<u8> = Vec::new();
+        let mut stream = response.bytes_stream();
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| format!("Stream error: {}", e))?;
+            pending.extend_from_slice(&chunk);
+
+            while let Some(newline) = pending.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=newline).collect();
+                let line = String::from_utf8_lossy(&line);
+                let data = match line.trim().strip_prefix("data:") {
+                    Some(data) => data.trim(),
+                    None => continue,
+                };
+                if data == "[DONE]" {
+                    return Ok(full_content);
+                }
+
+                let json: serde_json::Value = match serde_json::from_str(data) {
+                    Ok(json) => json,
+                    Err(_) => continue,
+                };
+
+                if json.get("error").is_some() {
+                    return Err(format!(
+                        "The server reported an error mid-response: {}",
+                        error_detail(&json).unwrap_or("unknown error")
+                    ));
+                }
+
+                if let Some(content) = json
+                    .get("choices")
+                    .and_then(|c| c.get(0))
+                    .and_then(|c| c.get("delta"))
+                    .and_then(|d| d.get("content"))
+                    .and_then(|c| c.as_str())
+                {
+                    if content.is_empty() {
+                        continue;
+                    }
+                    full_content.push_str(content);
+                    on_chunk(content)?;
+                }
+            }
+        }
+
+        Ok(full_content)
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<String, String> {
+        let response = self.post_chat(&request, false, COMPLETION_TIMEOUT).await?;
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+        json.get("choices")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("message"))
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .map(|c| c.to_string())
+            .ok_or("Invalid response format".to_string())
+    }
+}
+
+// top_k is not part of the OpenAI API, but llama.cpp and vLLM both accept it.
+// num_ctx is fixed when the server loads the model.
+fn chat_body(request: &CompletionRequest, stream: bool) -> serde_json::Value {
+    let messages: Vec<serde_json::Value> = request
+        .messages
+        .iter()
+        .map(|m| serde_json::json!({ "role": m.role, "content": m.content }))
+        .collect();
+
+    let settings = &request.settings;
+    let mut body = serde_json::json!({
+        "model": request.model,
+        "messages": messages,
+        "stream": stream,
+    });
+
+    if let Some(temperature) = settings.temperature {
+        body["temperature"] = temperature.into();
+    }
+    if let Some(top_p) = settings.top_p {
+        body["top_p"] = top_p.into();
+    }
+    if let Some(top_k) = settings.top_k {
+        body["top_k"] = top_k.into();
+    }
+    if let Some(max_tokens) = settings.max_tokens {
+        body["max_tokens"] = max_tokens.into();
+    }
+    if !settings.stop.is_empty() {
+        body["stop"] = settings.stop.clone().into();
+    }
+    if let Some(seed) = settings.seed {
+        body["seed"] = seed.into();
+    }
+
+    body
+}
+
+// Longest stretch of a non-JSON error body worth showing
+const ERROR_BODY_LIMIT: usize = 300;
+
+// Servers disagree on the error shape: {"error": {"message"}}, {"error": "..."} or {"detail": "..."}
+fn error_detail(body: &serde_json::Value) -> Option<&str> {
+    body.get("error")
+        .and_then(|e| e.get("message").or(Some(e)))
+        .or_else(|| body.get("detail"))
+        .and_then(|m| m.as_str())
+}
+
+// An unsuccessful response as a message that keeps the HTTP status and what the server said
+async fn response_error(response: reqwest::Response) -> String {
+    let status = response.status().as_u16();
+    let text = response.text().await.unwrap_or_default();
+    let json = serde_json::from_str::<serde_json::Value>(&text).ok();
+    let detail = match json.as_ref().and_then(error_detail) {
+        Some(detail) => detail.to_string(),
+        None if text.trim().is_empty() => "no details".to_string(),
+        None => text.trim().chars().take(ERROR_BODY_LIMIT).collect(),
+    };
+    error_message(status, &detail)
+}
+
+fn error_message(status: u16, detail: &str) -> String {
+    match status {
+        401 | 403 => format!(
+            "The server rejected the API key (HTTP {}): {}. Please check the key in settings.",
+            status, detail
+        ),
+        404 => format!("The server does not know that model or endpoint (HTTP 404): {}", detail),
+        429 => format!(
+            "The server is busy (HTTP 429, rate limited): {}. Please try again shortly.",
+            detail
+        ),
+        _ => format!("Server error (HTTP {}): {}", status, detail),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_config::GenerationSettings;
+    use crate::mock_server::{self, Response};
+    use crate::provider::ChatMessage;
+
+    fn request() -> CompletionRequest {
+        CompletionRequest {
+            model: "local-model".to_string(),
+            messages: vec![ChatMessage::user("Hi")],
+            settings: GenerationSettings::default(),
+        }
+    }
+
+    fn keyless(base_url: &str) -> OpenAiCompatibleProvider {
+        OpenAiCompatibleProvider::new(base_url, None, &KeySources::default())
+    }
+
+    #[tokio::test]
+    async fn stream_deltas_are_passed_on_until_done() {
+        let events = concat!(
+            ": keep-alive\n\n",
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"lo\"}}]}\n\n",
+            "data: [DONE]\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"after done\"}}]}\n\n",
+        );
+        let (base_url, server) = mock_server::serve(vec![Response::sse(events)]);
+
+        let mut chunks = Vec::new();
+        let content = keyless(&base_url)
+            .chat_stream(request(), &mut |chunk| {
+                chunks.push(chunk.to_string());
+                Ok(())
+            })
+            .await
+            .unwrap();
+        let requests = server.join().unwrap();
+
+        assert_eq!(content, "Hello");
+        assert_eq!(chunks, vec!["Hel", "lo"]);
+        assert!(requests[0].starts_with("POST /v1/chat/completions "));
+        assert!(requests[0].contains("\"stream\":true"));
+        assert!(!requests[0].contains("authorization:"));
+    }
+
+    #[tokio::test]
+    async fn error_in_the_stream_is_reported() {
+        let events = "data: {\"error\":{\"message\":\"context length exceeded\"}}\n\n";
+        let (base_url, server) = mock_server::serve(vec![Response::sse(events)]);
+
+        let result = keyless(&base_url).chat_stream(request(), &mut |_| Ok(())).await;
+        server.join().unwrap();
+
+        assert!(result.unwrap_err().contains("context length exceeded"));
+    }
+
+    #[tokio::test]
+    async fn non_json_errors_keep_their_status_and_body() {
+        let cases = [
+            (401, "Unauthorized", "API key"),
+            (404, "<html>Not Found</html>", "Not Found"),
+            (503, "", "no details"),
+        ];
+        for (status, body, expected) in cases {
+            let (base_url, server) = mock_server::serve(vec![Response {
+                status,
+                content_type: "text/plain",
+                body: body.to_string(),
+            }]);
+
+            let error = keyless(&base_url).list_models().await.unwrap_err();
+            server.join().unwrap();
+
+            assert!(error.contains(&format!("HTTP {}", status)), "{}", error);
+            assert!(error.contains(expected), "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn json_errors_use_the_server_message() {
+        let (base_url, server) = mock_server::serve(vec![Response::json(
+            500,
+            r#"{"error":{"message":"model crashed"}}"#,
+        )]);
+
+        let error = keyless(&base_url).complete(request()).await.unwrap_err();
+        server.join().unwrap();
+
+        assert_eq!(error, "Server error (HTTP 500): model crashed");
+    }
+
+    #[tokio::test]
+    async fn configured_credential_is_sent_as_bearer() {
+        let dir = std::env::temp_dir().join(format!("dataconfessional-openai-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key_file = dir.join("local.key");
+        std::fs::write(&key_file, "sk-local").unwrap();
+        let sources = KeySources {
+            credential_id: "local".to_string(),
+            api_key_env: None,
+            api_key_file: Some(key_file.display().to_string()),
+            index_dir: Some(dir),
+        };
+
+        let (base_url, server) = mock_server::serve(vec![Response::json(
+            200,
+            r#"{"data":[{"id":"qwen3:4b"},{"id":"llama3"}]}"#,
+        )]);
+        let provider = OpenAiCompatibleProvider::new(&format!("{}/v1", base_url), Some("local"), &sources);
+
+        let models = provider.list_models().await.unwrap();
+        let requests = server.join().unwrap();
+
+        assert_eq!(models, vec!["qwen3:4b", "llama3"]);
+        assert!(requests[0].starts_with("GET /v1/models "));
+        assert!(requests[0].contains("authorization: Bearer sk-local"));
+    }
+}
@@ -0,0 +1,270 @@
+// Organization-managed policy layered over the user's engine config
+// Read-only to the app: IT deploys engine-policy.json and every engine command checks it
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::net::IpAddr;
+
+use crate::app_paths;
+use crate::engine_config::EngineConfig;
+
+// Every field is optional; a missing field leaves that setting up to the user
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnginePolicy {
+    // Provider names such as "ollama" or "anthropic"
+    pub allowed_providers: Option<BTreeSet<String>>,
+    // Exact hosts, or "*.example.com" for any subdomain
+    pub allowed_base_url_hosts: Option<BTreeSet<String>>,
+    // Pack ids the user may activate. Setting this also locks pack editing.
+    pub approved_packs: Option<BTreeSet<String>>,
+    // false keeps every request on the local machine or network
+    pub allow_cloud_providers: Option<bool>,
+}
+
+// Config fields the UI should show as managed and read-only
+pub const LOCKED_PROVIDER: &str = "provider";
+pub const LOCKED_BASE_URL: &str = "base_url";
+pub const LOCKED_PACKS: &str = "packs";
+pub const LOCKED_ACTIVE_PACK: &str = "active_pack_id";
+
+// A missing file means no policy. A file that exists but cannot be read is an error rather
+// than silently unrestricted.
+pub fn load_policy() -> Result<Option<EnginePolicy>, String> {
+    let path = match app_paths::policy_path() {
+        Some(path) if path.exists() => path,
+        _ => return Ok(None),
+    };
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read organization policy {}: {}", path.display(), e))?;
+    let policy = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse organization policy {}: {}", path.display(), e))?;
+
+    Ok(Some(policy))
+}
+
+impl EnginePolicy {
+    pub fn locked_fields(&self) -> BTreeSet<&'static str> {
+        let mut locked = BTreeSet::new();
+        if self.allowed_providers.is_some() || self.allow_cloud_providers == Some(false) {
+            locked.insert(LOCKED_PROVIDER);
+        }
+        if self.allowed_base_url_hosts.is_some() || self.allow_cloud_providers == Some(false) {
+            locked.insert(LOCKED_BASE_URL);
+        }
+        if self.approved_packs.is_some() {
+            locked.insert(LOCKED_PACKS);
+            locked.insert(LOCKED_ACTIVE_PACK);
+        }
+        locked
+    }
+
+    pub fn packs_locked(&self) -> bool {
+        self.approved_packs.is_some()
+    }
+
+    pub fn is_pack_approved(&self, pack_id: &str) -> bool {
+        match &self.approved_packs {
+            Some(approved) => approved.contains(pack_id),
+            None => true,
+        }
+    }
+
+    pub fn check_provider(&self, provider: &str, base_url: &str) -> Result<(), String> {
+        if let Some(allowed) = &self.allowed_providers {
+            if !allowed.contains(provider) {
+                return Err(format!(
+                    "Provider '{}' is not allowed by your organization's policy",
+                    provider
+                ));
+            }
+        }
+
+        let host = base_url_host(base_url)?;
+
+        if let Some(allowed) = &self.allowed_base_url_hosts {
+            if !allowed.iter().any(|pattern| host_matches(pattern, &host)) {
+                return Err(format!(
+                    "Host '{}' is not allowed by your organization's policy",
+                    host
+                ));
+            }
+        }
+
+        if self.allow_cloud_providers == Some(false) && is_cloud(provider, &host) {
+            return Err(format!(
+                "Cloud providers are disabled by your organization's policy ({} at {})",
+                provider, host
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn check_pack(&self, pack_id: &str) -> Result<(), String> {
+        if self.is_pack_approved(pack_id) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Pack '{}' is not approved by your organization's policy",
+                pack_id
+            ))
+        }
+    }
+
+    pub fn check_pack_edit(&self) -> Result<(), String> {
+        if self.packs_locked() {
+            Err("Model packs are managed by your organization's policy and cannot be changed".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    // Every provider a request can reach: the primary and each fallback. Pack roles can only
+    // be routed to providers in that chain, so this covers the routes too.
+    pub fn check_config(&self, config: &EngineConfig) -> Result<(), String> {
+        self.check_provider(&config.provider, &config.base_url)?;
+        for fallback in &config.fallback_providers {
+            self.check_provider(&fallback.provider, &fallback.base_url)
+                .map_err(|e| format!("Fallback provider '{}': {}", fallback.id, e))?;
+        }
+        if let Some(pack_id) = &config.active_pack_id {
+            self.check_pack(pack_id)?;
+        }
+        Ok(())
+    }
+
+    // Bring a loaded config in line where that can be done without guessing: an active
+    // pack the policy does not approve is swapped for an approved one, or cleared
+    pub fn apply(&self, config: &mut EngineConfig) {
+        match &config.active_pack_id {
+            Some(pack_id) if !self.is_pack_approved(pack_id) => {}
+            _ => return,
+        }
+
+        config.active_pack_id = self
+            .approved_packs
+            .iter()
+            .flatten()
+            .find(|id| config.packs.contains_key(*id))
+            .cloned();
+    }
+}
+
+// Free-function forms for callers that do not hold a policy; no policy allows everything
+
+pub fn check_provider(provider: &str, base_url: &str) -> Result<(), String> {
+    match load_policy()? {
+        Some(policy) => policy.check_provider(provider, base_url),
+        None => Ok(()),
+    }
+}
+
+pub fn check_pack(pack_id: &str) -> Result<(), String> {
+    match load_policy()? {
+        Some(policy) => policy.check_pack(pack_id),
+        None => Ok(()),
+    }
+}
+
+pub fn check_pack_edit() -> Result<(), String> {
+    match load_policy()? {
+        Some(policy) => policy.check_pack_edit(),
+        None => Ok(()),
+    }
+}
+
+pub fn check_config(config: &EngineConfig) -> Result<(), String> {
+    match load_policy()? {
+        Some(policy) => policy.check_config(config),
+        None => Ok(()),
+    }
+}
+
+pub fn locked_fields(policy: Option<&EnginePolicy>) -> Vec<String> {
+    policy
+        .map(|p| p.locked_fields().into_iter().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn base_url_host(base_url: &str) -> Result<String, String> {
+    reqwest::Url::parse(base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(|h| h.trim_matches(['[', ']']).to_ascii_lowercase()))
+        .ok_or(format!("Invalid base_url '{}'", base_url))
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host.ends_with(&format!(".{}", domain)),
+        None => pattern == host,
+    }
+}
+
+// Anything that leaves the machine or private network counts as cloud. Anthropic is
+// always cloud whatever base_url says.
+fn is_cloud(provider: &str, host: &str) -> bool {
+    if provider == "anthropic" {
+        return true;
+    }
+    if host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") {
+        return false;
+    }
+
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => !(ip.is_loopback() || ip.is_private() || ip.is_link_local()),
+        // fc00::/7 is the IPv6 private range
+        Ok(IpAddr::V6(ip)) => !(ip.is_loopback() || (ip.segments()[0] & 0xfe00) == 0xfc00),
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_config::ProviderConfig;
+
+    fn local_only() -> EnginePolicy {
+        EnginePolicy {
+            allow_cloud_providers: Some(false),
+            ..EnginePolicy::default()
+        }
+    }
+
+    #[test]
+    fn disallowed_fallback_is_rejected() {
+        let mut config = EngineConfig::default();
+        assert!(local_only().check_config(&config).is_ok());
+
+        config.fallback_providers.push(ProviderConfig {
+            id: "cloud".to_string(),
+            provider: "anthropic".to_string(),
+            base_url: "https://api.anthropic.com".to_string(),
+            credential_id: None,
+            model: Some("claude-sonnet-4-5".to_string()),
+        });
+        let err = local_only().check_config(&config).unwrap_err();
+        assert!(err.contains("'cloud'"), "{}", err);
+    }
+
+    #[test]
+    fn allowed_hosts_apply_to_fallbacks() {
+        let policy = EnginePolicy {
+            allowed_base_url_hosts: Some(BTreeSet::from(["127.0.0.1".to_string()])),
+            ..EnginePolicy::default()
+        };
+        let mut config = EngineConfig::default();
+        config.fallback_providers.push(ProviderConfig {
+            id: "gpu-box".to_string(),
+            provider: "ollama".to_string(),
+            base_url: "http://192.168.1.20:11434".to_string(),
+            credential_id: None,
+            model: None,
+        });
+
+        assert!(policy.check_config(&config).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+// Engine provider abstraction
+// Everything the engine commands need from an LLM backend, so a new backend only has to
+// implement EngineProvider and be added to provider_for
+
+use async_trait::async_trait;
+use reqwest::header::HeaderValue;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+
+use crate::anthropic::AnthropicProvider;
+use crate::api_key::{self, KeySources};
+use crate::engine_config::{GenerationSettings, ProviderConfig};
+use crate::ollama::OllamaProvider;
+use crate::openai_compatible::OpenAiCompatibleProvider;
+
+#[derive(Debug, Clone)]
+pub struct ChatMessage {
+    pub role: String, // "system" | "user" | "assistant"
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self { role: "system".to_string(), content: content.into() }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self { role: "user".to_string(), content: content.into() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompletionRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub settings: GenerationSettings,
+}
+
+// A model the backend currently holds in memory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedModel {
+    pub name: String,
+    pub size_bytes: Option<u64>,
+    // How much of it sits in GPU memory
+    pub vram_bytes: Option<u64>,
+}
+
+// Called with each piece of streamed text as it arrives; an error stops the stream
+pub type ChunkHandler<'a> = dyn FnMut(&str) -> Result<(), String> + Send + 'a;
+
+#[async_trait]
+pub trait EngineProvider: Send + Sync {
+    // Ok when the backend is reachable and usable; otherwise a message for the user
+    async fn health(&self) -> Result<(), String>;
+
+    // Names of the models the backend can serve right now
+    async fn list_models(&self) -> Result<Vec<String>, String>;
+
+    // Size in bytes of the models the backend has, where it knows; hosted backends report none
+    async fn model_sizes(&self) -> Result<BTreeMap<String, u64>, String> {
+        Ok(BTreeMap::new())
+    }
+
+    // Server version for diagnostics, when the backend reports one
+    async fn version(&self) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    // Models loaded in memory right now; hosted backends report none
+    async fn loaded_models(&self) -> Result<Vec<LoadedModel>, String> {
+        Ok(Vec::new())
+    }
+
+    // Settings to send for a role: the pack's own, with this backend's defaults for the rest.
+    // The shipped defaults are tuned for local models; hosted backends override this.
+    fn generation_settings(
+        &self,
+        role: &str,
+        pack_settings: &GenerationSettings,
+    ) -> GenerationSettings {
+        pack_settings.or_defaults(&GenerationSettings::role_defaults(role))
+    }
+
+    // Try `key` with the cheapest authenticated request the backend offers, without storing it.
+    // The HTTP status it answered with, or None for backends that take no key.
+    async fn check_key(&self, _key: &str) -> Result<Option<u16>, String> {
+        Ok(None)
+    }
+
+    // Download a model so list_models includes it
+    async fn install_model(&self, model: &str) -> Result<(), String>;
+
+    // Stream a chat reply through `on_chunk` and return the full text
+    async fn chat_stream(
+        &self,
+        request: CompletionRequest,
+        on_chunk: &mut ChunkHandler<'_>,
+    ) -> Result<String, String>;
+
+    // Whole reply in one response
+    async fn complete(&self, request: CompletionRequest) -> Result<String, String>;
+}
+
+// `key_sources` comes from the same config snapshot as `config`
+pub fn provider_for(
+    config: &ProviderConfig,
+    key_sources: &KeySources,
+) -> Result<Box<dyn EngineProvider>, String> {
+    match config.provider.as_str() {
+        "ollama" => Ok(Box::new(OllamaProvider::new(&config.base_url))),
+        "anthropic" => Ok(Box::new(AnthropicProvider::new(
+            &config.base_url,
+            config.credential_id.as_deref().unwrap_or(api_key::DEFAULT_CREDENTIAL_ID),
+            key_sources,
+        ))),
+        "openai_compatible" => Ok(Box::new(OpenAiCompatibleProvider::new(
+            &config.base_url,
+            config.credential_id.as_deref(),
+            key_sources,
+        ))),
+        other => Err(format!("Unknown engine provider '{}'", other)),
+    }
+}
+
+// Header value that reqwest will keep out of its Debug output
+pub fn secret_header(value: &str) -> HeaderValue {
+    let mut header = HeaderValue::from_str(value).unwrap_or_else(|_| HeaderValue::from_static(""));
+    header.set_sensitive(true);
+    header
+}
+
+// "Bearer <token>" assembled in a buffer that is wiped once the header holds it
+pub fn bearer_header(token: &str) -> HeaderValue {
+    let mut value = Zeroizing::new(String::with_capacity("Bearer ".len() + token.len()));
+    value.push_str("Bearer ");
+    value.push_str(token);
+    secret_header(&value)
+}
@@ -0,0 +1,479 @@
+// Hardware-aware model pack recommendations
+// Estimates what each pack needs from the sizes of its models and compares that with this machine
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use crate::engine_config::{self, EngineConfig, ModelPack, ProviderConfig, PACK_ROLES, PRIMARY_PROVIDER_ID};
+use crate::policy::EnginePolicy;
+use crate::system_info::{bytes_to_gb, HardwareInfo};
+
+const BYTES_PER_GB: u64 = 1024 * 1024 * 1024;
+// KV cache and runtime buffers on top of the weights
+const CONTEXT_OVERHEAD: f64 = 1.2;
+// Left for the OS and the app itself when a model runs from system RAM
+const OS_HEADROOM_GB: f64 = 2.0;
+// Apple Silicon lets the GPU use about this much of the unified memory
+const UNIFIED_MEMORY_SHARE: f64 = 0.75;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackRecommendation {
+    pub pack_id: String,
+    pub label: String,
+    pub recommended: bool,
+    // 0-100, higher runs better on this machine; only meaningful for ordering
+    pub score: u32,
+    // Memory the pack's local models need while loaded, when their sizes are known
+    pub memory_needed_gb: Option<f64>,
+    // Still to download before the pack can be used
+    pub download_gb: Option<f64>,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackRecommendations {
+    pub hardware: HardwareInfo,
+    // Best first
+    pub packs: Vec<PackRecommendation>,
+    pub recommended_pack_id: Option<String>,
+}
+
+// Models served by an Ollama on this machine use its RAM, GPU and disk. Anything else (a LAN
+// server, a hosted API, an OpenAI-compatible server that loads its own models) does not.
+pub fn runs_on_this_machine(entry: &ProviderConfig) -> bool {
+    if entry.provider != "ollama" {
+        return false;
+    }
+
+    let host = reqwest::Url::parse(&entry.base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(|h| h.trim_matches(['[', ']']).to_ascii_lowercase()));
+    match host.as_deref() {
+        Some("localhost") => true,
+        Some(host) => host.parse::<IpAddr>().map(|ip| ip.is_loopback()).unwrap_or(false),
+        None => false,
+    }
+}
+
+// Score every pack in the config. `installed` holds, per provider id, the sizes of the
+// models that provider already has.
+pub fn recommend_packs(
+    config: &EngineConfig,
+    policy: Option<&EnginePolicy>,
+    hardware: &HardwareInfo,
+    installed: &BTreeMap<String, BTreeMap<String, u64>>,
+) -> PackRecommendations {
+    let mut packs: Vec<PackRecommendation> = config
+        .packs
+        .iter()
+        .map(|(pack_id, pack)| {
+            let mut recommendation = assess_pack(config, pack_id, pack, hardware, installed);
+            if let Some(policy) = policy {
+                if !policy.is_pack_approved(pack_id) {
+                    recommendation.recommended = false;
+                    recommendation
+                        .reasons
+                        .insert(0, "Not approved by your organization's policy".to_string());
+                }
+            }
+            recommendation
+        })
+        .collect();
+
+    packs.sort_by(|a, b| {
+        b.recommended
+            .cmp(&a.recommended)
+            .then(b.score.cmp(&a.score))
+            .then(a.pack_id.cmp(&b.pack_id))
+    });
+    let recommended_pack_id = packs.iter().find(|p| p.recommended).map(|p| p.pack_id.clone());
+
+    PackRecommendations {
+        hardware: hardware.clone(),
+        packs,
+        recommended_pack_id,
+    }
+}
+
+// Size of one model: what the provider reports if installed, otherwise estimated from its name
+struct ModelSize {
+    bytes: Option<u64>,
+    installed: bool,
+}
+
+fn assess_pack(
+    config: &EngineConfig,
+    pack_id: &str,
+    pack: &ModelPack,
+    hardware: &HardwareInfo,
+    installed: &BTreeMap<String, BTreeMap<String, u64>>,
+) -> PackRecommendation {
+    let mut reasons = Vec::new();
+    let mut blocked = false;
+    let mut score: i32 = 100;
+
+    // The largest chat or report model and the embedding model are loaded side by side.
+    // Models of unknown size are left out of these and listed instead.
+    let mut largest_chat: u64 = 0;
+    let mut embedding: u64 = 0;
+    let mut download: u64 = 0;
+    let mut unknown_sizes = Vec::new();
+    let mut counted_downloads = Vec::new();
+    let mut any_local = false;
+
+    for role_name in PACK_ROLES {
+        let role = match pack.role(role_name) {
+            Ok(role) => role,
+            Err(_) => continue,
+        };
+        let provider_id = role.provider_id.as_deref().unwrap_or(PRIMARY_PROVIDER_ID);
+        let entry = match config.provider_by_id(provider_id) {
+            Ok(entry) => entry,
+            Err(e) => {
+                reasons.push(e);
+                blocked = true;
+                continue;
+            }
+        };
+
+        if !runs_on_this_machine(&entry) {
+            let note = format!(
+                "{} runs on {} ({}), so this machine's hardware does not limit it",
+                role.model, entry.id, entry.provider
+            );
+            if !reasons.contains(&note) {
+                reasons.push(note);
+            }
+            continue;
+        }
+        any_local = true;
+
+        let size = model_size(&role.model, installed.get(provider_id));
+        let bytes = match size.bytes {
+            Some(bytes) => bytes,
+            None => {
+                if !unknown_sizes.contains(&role.model) {
+                    unknown_sizes.push(role.model.clone());
+                }
+                continue;
+            }
+        };
+
+        if !size.installed && !counted_downloads.contains(&role.model) {
+            download += bytes;
+            counted_downloads.push(role.model.clone());
+        }
+        let slot = if role_name == "embedding" { &mut embedding } else { &mut largest_chat };
+        *slot = (*slot).max(bytes);
+    }
+
+    let memory_needed = if largest_chat + embedding > 0 {
+        Some((largest_chat as f64 * CONTEXT_OVERHEAD) as u64 + embedding)
+    } else {
+        None
+    };
+
+    if !unknown_sizes.is_empty() {
+        score -= 10;
+        reasons.push(format!(
+            "Size of {} is unknown until installed; the estimate leaves it out",
+            unknown_sizes.join(", ")
+        ));
+    }
+
+    if let Some(needed) = memory_needed {
+        let (fit_blocked, penalty, reason) = assess_memory(bytes_to_gb(needed), hardware);
+        blocked |= fit_blocked;
+        score -= penalty;
+        reasons.push(reason);
+    }
+
+    if any_local && needs_cpu(memory_needed, hardware) && lacks_vector_units(hardware) {
+        score -= 20;
+        reasons.push("This CPU has no AVX2, so running models on the CPU will be very slow".to_string());
+    }
+
+    match hardware.free_disk_gb {
+        _ if !any_local => {}
+        _ if download == 0 && unknown_sizes.is_empty() => {
+            reasons.push("All models are already installed".to_string())
+        }
+        Some(free_gb) if download > 0 => {
+            let download_gb = bytes_to_gb(download);
+            if download_gb > free_gb {
+                blocked = true;
+                reasons.push(format!(
+                    "Needs a ~{} GB download, but only {} GB is free for models",
+                    download_gb, free_gb
+                ));
+            } else {
+                reasons.push(format!(
+                    "Needs a ~{} GB download; {} GB is free for models",
+                    download_gb, free_gb
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    PackRecommendation {
+        pack_id: pack_id.to_string(),
+        label: pack.label.clone(),
+        recommended: !blocked,
+        score: if blocked { 0 } else { score.clamp(0, 100) as u32 },
+        memory_needed_gb: memory_needed.map(bytes_to_gb),
+        download_gb: Some(download).filter(|_| any_local).map(bytes_to_gb),
+        reasons,
+    }
+}
+
+// Where a model of `needed_gb` would run: (blocked, score penalty, reason)
+fn assess_memory(needed_gb: f64, hardware: &HardwareInfo) -> (bool, i32, String) {
+    let gpu = &hardware.gpu;
+    let ram_gb = hardware.total_ram_gb;
+    let gpu_memory_gb = gpu_memory_gb(hardware);
+
+    if let Some(vram) = gpu_memory_gb {
+        if needed_gb <= vram {
+            return (
+                false,
+                0,
+                format!("Needs ~{} GB and fits in the {} GB of GPU memory on {}", needed_gb, vram, gpu.description),
+            );
+        }
+    }
+
+    let ram_gb = match ram_gb {
+        Some(ram) => ram,
+        None => {
+            return (
+                false,
+                30,
+                format!("Needs ~{} GB; could not read how much memory this machine has", needed_gb),
+            )
+        }
+    };
+
+    if needed_gb + OS_HEADROOM_GB > ram_gb {
+        let gpu_part = match gpu_memory_gb {
+            Some(vram) => format!("{} GB of GPU memory", vram),
+            None => "no GPU".to_string(),
+        };
+        return (
+            true,
+            0,
+            format!("Needs ~{} GB, you have {} GB RAM and {}", needed_gb, ram_gb, gpu_part),
+        );
+    }
+
+    match gpu_memory_gb {
+        Some(vram) => (
+            false,
+            25,
+            format!(
+                "Needs ~{} GB, more than the {} GB of GPU memory; runs partly on the CPU from {} GB RAM, so replies are slower",
+                needed_gb, vram, ram_gb
+            ),
+        ),
+        // On the CPU, every extra gigabyte of weights slows each reply down further
+        None => (
+            false,
+            40 + (needed_gb * 2.0).min(20.0) as i32,
+            format!(
+                "Needs ~{} GB and fits in {} GB RAM, but no GPU was found, so it runs on the CPU and replies are slow",
+                needed_gb, ram_gb
+            ),
+        ),
+    }
+}
+
+// Dedicated VRAM, or the GPU's share of unified memory on Apple Silicon
+fn gpu_memory_gb(hardware: &HardwareInfo) -> Option<f64> {
+    if hardware.gpu.vendor == "apple" {
+        return hardware
+            .total_ram_gb
+            .map(|ram| (ram * UNIFIED_MEMORY_SHARE * 10.0).round() / 10.0);
+    }
+    hardware.gpu.vram_gb.map(f64::from)
+}
+
+fn needs_cpu(memory_needed: Option<u64>, hardware: &HardwareInfo) -> bool {
+    match (memory_needed, gpu_memory_gb(hardware)) {
+        (Some(needed), Some(vram)) => bytes_to_gb(needed) > vram,
+        (_, None) => true,
+        (None, Some(_)) => false,
+    }
+}
+
+fn lacks_vector_units(hardware: &HardwareInfo) -> bool {
+    hardware.cpu_arch == "x86_64" && !hardware.cpu_features.iter().any(|f| f == "avx2")
+}
+
+fn model_size(model: &str, installed: Option<&BTreeMap<String, u64>>) -> ModelSize {
+    let name = with_default_tag(model);
+    let installed_size = installed.and_then(|sizes| {
+        sizes
+            .iter()
+            .find(|(installed_name, _)| with_default_tag(installed_name) == name)
+            .map(|(_, size)| *size)
+    });
+
+    match installed_size {
+        Some(bytes) => ModelSize { bytes: Some(bytes), installed: true },
+        None => ModelSize {
+            bytes: engine_config::builtin_model_bytes(model).or_else(|| estimate_model_bytes(model)),
+            installed: false,
+        },
+    }
+}
+
+// Ollama treats "llama3" and "llama3:latest" as the same model
+fn with_default_tag(model: &str) -> String {
+    let last = model.rsplit('/').next().unwrap_or(model);
+    if last.contains(':') {
+        model.to_ascii_lowercase()
+    } else {
+        format!("{}:latest", model.to_ascii_lowercase())
+    }
+}
+
+// Guess the download size from a parameter count in the name, such as "qwen3:4b",
+// "llama-3.1-8b-instruct" or "mixtral:8x7b". Ollama's default tags are 4-bit quantized.
+pub fn estimate_model_bytes(model: &str) -> Option<u64> {
+    let lower = model.to_ascii_lowercase();
+    let bytes_per_param = if lower.contains("fp16") || lower.contains("f16") {
+        2.0
+    } else if lower.contains("q8") {
+        1.07
+    } else if lower.contains("q6") {
+        0.82
+    } else if lower.contains("q5") {
+        0.7
+    } else {
+        0.6
+    };
+
+    let params_billions = lower
+        .split([':', '-', '_', '/'])
+        .find_map(parameter_count)?;
+
+    Some((params_billions * bytes_per_param * BYTES_PER_GB as f64) as u64)
+}
+
+// "8b" -> 8, "0.5b" -> 0.5, "8x7b" -> 56
+fn parameter_count(token: &str) -> Option<f64> {
+    let number = token.strip_suffix('b')?;
+    match number.split_once('x') {
+        Some((experts, size)) => Some(experts.parse::<f64>().ok()? * size.parse::<f64>().ok()?),
+        None => number.parse::<f64>().ok().filter(|n| *n > 0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::GpuSummary;
+
+    fn hardware(ram_gb: Option<f64>, vendor: &str, vram_gb: Option<u32>) -> HardwareInfo {
+        HardwareInfo {
+            total_ram_gb: ram_gb,
+            available_ram_gb: ram_gb,
+            cpu_arch: "x86_64".to_string(),
+            cpu_cores: 8,
+            cpu_features: vec!["avx2".to_string()],
+            models_dir: None,
+            free_disk_gb: Some(100.0),
+            gpu: GpuSummary {
+                vendor: vendor.to_string(),
+                vram_gb,
+                gpus: Vec::new(),
+                cpu_only: vram_gb.is_none() && vendor != "apple",
+                description: "Test GPU".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn parameter_count_reads_sizes_and_expert_counts() {
+        assert_eq!(parameter_count("8b"), Some(8.0));
+        assert_eq!(parameter_count("0.5b"), Some(0.5));
+        assert_eq!(parameter_count("8x7b"), Some(56.0));
+        assert_eq!(parameter_count("0b"), None);
+        assert_eq!(parameter_count("web"), None);
+        assert_eq!(parameter_count("8"), None);
+    }
+
+    #[test]
+    fn estimate_uses_parameter_count_and_quantization() {
+        assert_eq!(estimate_model_bytes("qwen3:4b"), Some((4.0 * 0.6 * BYTES_PER_GB as f64) as u64));
+        assert_eq!(
+            estimate_model_bytes("llama-3.1-8b-instruct-q8_0"),
+            Some((8.0 * 1.07 * BYTES_PER_GB as f64) as u64)
+        );
+        assert_eq!(estimate_model_bytes("gurubot/glm-4.6v-flash-gguf:q4_k_m"), None);
+    }
+
+    #[test]
+    fn every_builtin_pack_model_has_a_size() {
+        for (pack_id, pack) in engine_config::builtin_packs() {
+            for role_name in PACK_ROLES {
+                if let Ok(role) = pack.role(role_name) {
+                    let size = model_size(&role.model, None);
+                    assert!(size.bytes.is_some(), "{} in {} has no size", role.model, pack_id);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn installed_size_wins_over_the_hint() {
+        let installed = BTreeMap::from([("qwen3:4b".to_string(), 42)]);
+        let size = model_size("qwen3:4b", Some(&installed));
+        assert_eq!(size.bytes, Some(42));
+        assert!(size.installed);
+    }
+
+    #[test]
+    fn assess_memory_fits_in_vram() {
+        let (blocked, penalty, _) = assess_memory(6.0, &hardware(Some(32.0), "nvidia", Some(8)));
+        assert!(!blocked);
+        assert_eq!(penalty, 0);
+    }
+
+    #[test]
+    fn assess_memory_uses_unified_memory_on_apple() {
+        let (blocked, penalty, _) = assess_memory(10.0, &hardware(Some(16.0), "apple", None));
+        assert!(!blocked);
+        assert_eq!(penalty, 0);
+    }
+
+    #[test]
+    fn assess_memory_spills_to_ram() {
+        let (blocked, penalty, _) = assess_memory(12.0, &hardware(Some(32.0), "nvidia", Some(8)));
+        assert!(!blocked);
+        assert_eq!(penalty, 25);
+    }
+
+    #[test]
+    fn assess_memory_on_cpu_scales_with_size() {
+        let (blocked, penalty, _) = assess_memory(4.0, &hardware(Some(32.0), "none", None));
+        assert!(!blocked);
+        assert_eq!(penalty, 48);
+        let (_, penalty, _) = assess_memory(16.0, &hardware(Some(32.0), "none", None));
+        assert_eq!(penalty, 60);
+    }
+
+    #[test]
+    fn assess_memory_blocks_without_headroom() {
+        let (blocked, _, _) = assess_memory(7.0, &hardware(Some(8.0), "none", None));
+        assert!(blocked);
+    }
+
+    #[test]
+    fn assess_memory_with_unknown_ram() {
+        let (blocked, penalty, _) = assess_memory(12.0, &hardware(None, "nvidia", Some(8)));
+        assert!(!blocked);
+        assert_eq!(penalty, 30);
+    }
+}
@@ -0,0 +1,186 @@
+// Hardware facts used to judge which model packs this machine can run
+// Memory, CPU features and free disk in the Ollama model directory, plus the detected GPUs
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::gpu::{self, GpuSummary};
+
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareInfo {
+    pub total_ram_gb: Option<f64>,
+    pub available_ram_gb: Option<f64>,
+    pub cpu_arch: String,
+    pub cpu_cores: usize,
+    // Instruction set extensions that matter for CPU inference, e.g. "avx2"
+    pub cpu_features: Vec<String>,
+    pub models_dir: Option<String>,
+    pub free_disk_gb: Option<f64>,
+    pub gpu: GpuSummary,
+}
+
+// Runs external commands for GPU detection, so call it off the async runtime
+pub fn detect_hardware() -> HardwareInfo {
+    let (total_ram, available_ram) = memory_bytes();
+    let models_dir = ollama_models_dir();
+    let free_disk = models_dir.as_deref().and_then(free_disk_bytes);
+
+    HardwareInfo {
+        total_ram_gb: total_ram.map(bytes_to_gb),
+        available_ram_gb: available_ram.map(bytes_to_gb),
+        cpu_arch: std::env::consts::ARCH.to_string(),
+        cpu_cores: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        cpu_features: cpu_features(),
+        models_dir: models_dir.map(|dir| dir.display().to_string()),
+        free_disk_gb: free_disk.map(bytes_to_gb),
+        gpu: gpu::detect_gpus(),
+    }
+}
+
+// Rounded to one decimal place, which is all the UI and the reasons show
+pub fn bytes_to_gb(bytes: u64) -> f64 {
+    (bytes as f64 / BYTES_PER_GB * 10.0).round() / 10.0
+}
+
+// Where a local Ollama keeps its models: OLLAMA_MODELS, then ~/.ollama/models, then the
+// Linux service install. The first that exists wins; otherwise the per-user default.
+pub fn ollama_models_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("OLLAMA_MODELS").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+
+    let home = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|d| !d.is_empty())
+        .map(|home| PathBuf::from(home).join(".ollama").join("models"));
+    let candidates = home
+        .iter()
+        .cloned()
+        .chain(cfg!(target_os = "linux").then(|| PathBuf::from("/usr/share/ollama/.ollama/models")));
+
+    candidates
+        .into_iter()
+        .find(|dir| dir.exists())
+        .or(home)
+}
+
+// Free space on the disk holding `path`. The directory may not exist before the first
+// download, so the nearest existing parent is measured instead.
+pub fn free_disk_bytes(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|dir| dir.exists())?;
+    platform_free_disk(existing)
+}
+
+#[cfg(unix)]
+fn platform_free_disk(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    unsafe {
+        let mut stat: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(c_path.as_ptr(), &mut stat) != 0 {
+            return None;
+        }
+        Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+    }
+}
+
+#[cfg(windows)]
+fn platform_free_disk(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr;
+    use winapi::um::fileapi::GetDiskFreeSpaceExW;
+    use winapi::um::winnt::ULARGE_INTEGER;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    unsafe {
+        let mut available: ULARGE_INTEGER = std::mem::zeroed();
+        if GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, ptr::null_mut(), ptr::null_mut()) == 0 {
+            return None;
+        }
+        Some(*available.QuadPart())
+    }
+}
+
+// (total, available) physical memory in bytes
+#[cfg(target_os = "linux")]
+fn memory_bytes() -> (Option<u64>, Option<u64>) {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").unwrap_or_default();
+    (meminfo_value(&meminfo, "MemTotal"), meminfo_value(&meminfo, "MemAvailable"))
+}
+
+// Lines look like "MemTotal:       16318440 kB"
+#[cfg(target_os = "linux")]
+fn meminfo_value(meminfo: &str, key: &str) -> Option<u64> {
+    meminfo.lines().find_map(|line| {
+        let rest = line.strip_prefix(key)?.strip_prefix(':')?;
+        let kb: u64 = rest.trim().trim_end_matches("kB").trim().parse().ok()?;
+        Some(kb * 1024)
+    })
+}
+
+// macOS has no cheap equivalent of MemAvailable, so only the total is reported
+#[cfg(target_os = "macos")]
+fn memory_bytes() -> (Option<u64>, Option<u64>) {
+    let total = std::process::Command::new("sysctl")
+        .args(["-n", "hw.memsize"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok());
+    (total, None)
+}
+
+#[cfg(windows)]
+fn memory_bytes() -> (Option<u64>, Option<u64>) {
+    use winapi::um::sysinfoapi::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    unsafe {
+        let mut status: MEMORYSTATUSEX = std::mem::zeroed();
+        status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+        if GlobalMemoryStatusEx(&mut status) == 0 {
+            return (None, None);
+        }
+        (Some(status.ullTotalPhys), Some(status.ullAvailPhys))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn memory_bytes() -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
+
+#[cfg(target_arch = "x86_64")]
+fn cpu_features() -> Vec<String> {
+    let mut features = Vec::new();
+    if is_x86_feature_detected!("avx") {
+        features.push("avx");
+    }
+    if is_x86_feature_detected!("avx2") {
+        features.push("avx2");
+    }
+    if is_x86_feature_detected!("fma") {
+        features.push("fma");
+    }
+    if is_x86_feature_detected!("f16c") {
+        features.push("f16c");
+    }
+    if is_x86_feature_detected!("avx512f") {
+        features.push("avx512f");
+    }
+    features.into_iter().map(str::to_string).collect()
+}
+
+// Every 64-bit ARM core has NEON, which is what llama.cpp builds on
+#[cfg(target_arch = "aarch64")]
+fn cpu_features() -> Vec<String> {
+    vec!["neon".to_string()]
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn cpu_features() -> Vec<String> {
+    Vec::new()
+}